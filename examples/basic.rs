@@ -4,6 +4,7 @@ use bevy_defer::AsyncPlugin;
 use bevy_inspector_egui::bevy_egui::EguiPlugin;
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 use bevy_la_mesa::events::{DeckShuffle, DrawToHand, RenderDeck};
+use bevy_la_mesa::shuffle::ShuffleStyle;
 use bevy_la_mesa::{CardMetadata, DeckArea, HandArea, LaMesaPlugin, LaMesaPluginSettings};
 
 // // Main
@@ -13,7 +14,7 @@ fn main() {
         .add_plugins(LaMesaPlugin::<PokerCard>::default())
         .add_plugins(AsyncPlugin::default_settings())
         .add_systems(Startup, (setup, setup_ui))
-        .add_systems(Update, (button_system, start_game))
+        .add_systems(Update, (button_system, start_game, cycle_shuffle_style))
         .add_plugins((
             EguiPlugin::default(),
             WorldInspectorPlugin::default().run_if(input_toggle_active(false, KeyCode::Escape)),
@@ -21,13 +22,31 @@ fn main() {
         .insert_resource(LaMesaPluginSettings { num_players: 1 })
         .insert_resource(GameState {
             game_started: false,
+            shuffle_style: ShuffleStyle::default(),
         })
         .run();
 }
 
 #[derive(Resource)]
-struct GameState {
+pub struct GameState {
     game_started: bool,
+    shuffle_style: ShuffleStyle,
+}
+
+/// Press S to try the next shuffle style.
+fn cycle_shuffle_style(keys: Res<ButtonInput<KeyCode>>, mut game_state: ResMut<GameState>) {
+    if !keys.just_pressed(KeyCode::KeyS) {
+        return;
+    }
+
+    game_state.shuffle_style = match game_state.shuffle_style {
+        ShuffleStyle::Alternate => ShuffleStyle::Riffle,
+        ShuffleStyle::Riffle => ShuffleStyle::Overhand,
+        ShuffleStyle::Overhand => ShuffleStyle::Cut,
+        ShuffleStyle::Cut => ShuffleStyle::Wash,
+        ShuffleStyle::Wash => ShuffleStyle::Alternate,
+    };
+    info!("shuffle style: {:?}", game_state.shuffle_style);
 }
 
 /// set up lights and scene
@@ -124,6 +143,7 @@ pub fn button_system(
         >,
     )>,
     decks: Query<(Entity, &DeckArea)>,
+    game_state: Res<GameState>,
     mut text_query: Query<&mut Text>,
     mut ew_shuffle: MessageWriter<DeckShuffle>,
     mut ew_draw: MessageWriter<DrawToHand>,
@@ -139,8 +159,10 @@ pub fn button_system(
                 // border_color.0 = RED.into();
 
                 ew_shuffle.write(DeckShuffle {
-                    deck_entity: deck_entity.clone(),
-                    duration: 8,
+                    deck_entity,
+                    style: game_state.shuffle_style,
+                    seed: None,
+                    timing: None,
                 });
             }
             Interaction::Hovered => {
@@ -164,9 +186,10 @@ pub fn button_system(
                 // border_color.0 = RED.into();
 
                 ew_draw.write(DrawToHand {
-                    deck_entity: deck_entity.clone(),
+                    deck_entity,
                     num_cards: 5,
                     player: 1,
+                    timing: None,
                 });
             }
             Interaction::Hovered => {
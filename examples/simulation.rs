@@ -0,0 +1,192 @@
+//! Plays bot against bot without a window, as fast as the table allows, and prints who wins
+//! more often.
+
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use bevy_la_mesa::ai::{AiAction, AiPlayer, AiPlugin, AiSeats, AiView};
+use bevy_la_mesa::events::{Deal, DealOrder, RenderDeck};
+use bevy_la_mesa::sync::TableCommand;
+use bevy_la_mesa::turn::{Phase, TurnPlugin, TurnSettings};
+use bevy_la_mesa::{
+    Card, CardMetadata, CardOnTable, DeckArea, HandArea, LaMesaPlugin, LaMesaPluginSettings,
+    PlayArea,
+};
+use rand::{seq::SliceRandom, SeedableRng};
+use std::time::Duration;
+
+const MATCHES: u64 = 200;
+const DECK: usize = 1;
+
+fn main() {
+    let mut wins = [0; 2];
+    let mut draws = 0;
+    let mut totals = [0; 2];
+
+    for seed in 0..MATCHES {
+        let [greedy, cautious] = play_match(seed);
+        totals[0] += greedy;
+        totals[1] += cautious;
+        match greedy.cmp(&cautious) {
+            std::cmp::Ordering::Greater => wins[0] += 1,
+            std::cmp::Ordering::Less => wins[1] += 1,
+            std::cmp::Ordering::Equal => draws += 1,
+        }
+    }
+
+    println!("{MATCHES} matches");
+    println!(
+        "greedy:   {} wins, {:.1} points on average",
+        wins[0],
+        totals[0] as f32 / MATCHES as f32
+    );
+    println!(
+        "cautious: {} wins, {:.1} points on average",
+        wins[1],
+        totals[1] as f32 / MATCHES as f32
+    );
+    println!("draws:    {draws}");
+}
+
+/// Play one match and return the points of both players.
+fn play_match(seed: u64) -> [u32; 2] {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, StatesPlugin))
+        .add_plugins(LaMesaPlugin::<NumberCard>::headless())
+        .add_plugins(TurnPlugin {
+            settings: TurnSettings {
+                players: vec![1, 2],
+                phases: vec![Phase::Draw, Phase::Main],
+                ..default()
+            },
+        })
+        .add_plugins(AiPlugin::<NumberCard>::default())
+        .insert_resource(LaMesaPluginSettings { num_players: 2 });
+
+    let mut seats = AiSeats::<NumberCard>::default();
+    seats.think_delay = Duration::ZERO;
+    seats.add(1, Greedy);
+    seats.add(2, Cautious);
+    app.insert_resource(seats);
+
+    let world = app.world_mut();
+    let deck_entity = world
+        .spawn((Transform::default(), DeckArea { marker: DECK }))
+        .id();
+    for player in [1, 2] {
+        world.spawn((Transform::default(), HandArea { player }));
+        world.spawn((Transform::default(), PlayArea { marker: 1, player }));
+    }
+
+    let mut deck: Vec<NumberCard> = (1..=10)
+        .chain(1..=10)
+        .map(|value| NumberCard { value })
+        .collect();
+    deck.shuffle(&mut rand::rngs::StdRng::seed_from_u64(seed));
+    let num_cards = deck.len();
+    world.write_message(RenderDeck { deck_entity, deck });
+    world.write_message(Deal {
+        deck_entity,
+        players: vec![1, 2],
+        cards_each: 3,
+        order: DealOrder::RoundRobin,
+        timing: None,
+    });
+
+    app.finish();
+    app.cleanup();
+
+    // every card ends up on the table
+    for _ in 0..10_000 {
+        app.update();
+
+        let world = app.world_mut();
+        let mut q_cards = world.query::<(&Card<NumberCard>, Option<&CardOnTable>)>();
+        let on_table: Vec<(u32, usize)> = q_cards
+            .iter(world)
+            .filter_map(|(card, on_table)| {
+                on_table.map(|on_table| (card.data.value, on_table.player))
+            })
+            .collect();
+        if on_table.len() == num_cards {
+            let points = |player| {
+                on_table
+                    .iter()
+                    .filter(|(_, p)| *p == player)
+                    .map(|(value, _)| value)
+                    .sum()
+            };
+            return [points(1), points(2)];
+        }
+    }
+
+    panic!("match {seed} did not finish");
+}
+
+#[derive(Clone, Debug)]
+struct NumberCard {
+    value: u32,
+}
+
+impl CardMetadata for NumberCard {
+    type Output = NumberCard;
+
+    fn front_image_filename(&self) -> String {
+        String::new()
+    }
+
+    fn back_image_filename(&self) -> String {
+        String::new()
+    }
+}
+
+/// Draw one card while the deck lasts, then play the highest card in hand.
+struct Greedy;
+
+/// Draw one card while the deck lasts, then play the lowest card in hand.
+struct Cautious;
+
+impl AiPlayer<NumberCard> for Greedy {
+    fn think(&mut self, view: &AiView<NumberCard>) -> Vec<AiAction<NumberCard>> {
+        let card = view.hand.iter().max_by_key(|(_, card)| card.value);
+        turn(view, card.map(|(id, _)| *id))
+    }
+}
+
+impl AiPlayer<NumberCard> for Cautious {
+    fn think(&mut self, view: &AiView<NumberCard>) -> Vec<AiAction<NumberCard>> {
+        let card = view.hand.iter().min_by_key(|(_, card)| card.value);
+        turn(view, card.map(|(id, _)| *id))
+    }
+}
+
+fn turn(
+    view: &AiView<NumberCard>,
+    card: Option<bevy_la_mesa::CardId>,
+) -> Vec<AiAction<NumberCard>> {
+    match view.phase {
+        // the hand is filled up to `num_cards`, one more than it holds draws a single card
+        Phase::Draw if view.decks.get(&DECK).is_some_and(|n| *n > 0) => vec![
+            AiAction::Table(TableCommand::DrawToHand {
+                deck: DECK,
+                num_cards: view.hand.len() + 1,
+                player: view.player,
+            }),
+            AiAction::NextPhase,
+        ],
+        Phase::Main => {
+            let mut actions: Vec<AiAction<NumberCard>> = card
+                .map(|card| {
+                    AiAction::Table(TableCommand::PlaceCardOnTable {
+                        card,
+                        marker: 1,
+                        player: view.player,
+                    })
+                })
+                .into_iter()
+                .collect();
+            actions.push(AiAction::EndTurn);
+            actions
+        }
+        _ => vec![AiAction::NextPhase],
+    }
+}
@@ -0,0 +1,184 @@
+use bevy::prelude::*;
+use std::{collections::HashMap, fmt::Debug, marker::PhantomData, time::Duration};
+
+use crate::queue::OperationQueue;
+use crate::sync::{apply, TableCommand, TableSync, TableWriters};
+use crate::table::TableQuery;
+use crate::turn::{CurrentPlayer, EndTurn, NextPhase, Phase, TurnState};
+use crate::{CardId, CardMetadata, DeckArea, PlayArea};
+
+/// What a bot sees of the table: its own hand, the cards on the play areas and how many cards
+/// are left in every deck. Other hands and the order of the decks stay hidden.
+pub struct AiView<'a, T> {
+    pub player: usize,
+    pub phase: Phase,
+    /// Cards in hand, left to right.
+    pub hand: Vec<(CardId, &'a T)>,
+    /// Cards on every play area, keyed by `(marker, player)`, bottom card first.
+    pub table: HashMap<(usize, usize), Vec<(CardId, &'a T)>>,
+    /// Number of cards in every deck, keyed by marker.
+    pub decks: HashMap<usize, usize>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AiAction<T> {
+    Table(TableCommand<T>),
+    NextPhase,
+    EndTurn,
+}
+
+/// Bot playing a seat at the table.
+pub trait AiPlayer<T>: Send + Sync + 'static {
+    /// Actions for the current phase. Called again after the table settles until it returns
+    /// [`AiAction::NextPhase`] or [`AiAction::EndTurn`]; returning nothing passes the phase.
+    fn think(&mut self, view: &AiView<T>) -> Vec<AiAction<T>>;
+}
+
+/// Seats played by bots.
+#[derive(Resource)]
+pub struct AiSeats<T> {
+    seats: HashMap<usize, Box<dyn AiPlayer<T>>>,
+    /// How long a bot waits before acting, once the table has settled.
+    pub think_delay: Duration,
+}
+
+impl<T> Default for AiSeats<T> {
+    fn default() -> Self {
+        Self {
+            seats: HashMap::new(),
+            think_delay: Duration::from_millis(600),
+        }
+    }
+}
+
+impl<T> AiSeats<T> {
+    pub fn add(&mut self, player: usize, ai: impl AiPlayer<T>) {
+        self.seats.insert(player, Box::new(ai));
+    }
+
+    pub fn remove(&mut self, player: usize) {
+        self.seats.remove(&player);
+    }
+
+    pub fn is_ai(&self, player: usize) -> bool {
+        self.seats.contains_key(&player)
+    }
+}
+
+pub fn handle_ai_players<T>(
+    mut seats: ResMut<AiSeats<T>>,
+    current: Res<CurrentPlayer>,
+    turn: Res<TurnState>,
+    phase: Res<State<Phase>>,
+    table: TableQuery<T>,
+    q_play_areas: Query<&PlayArea>,
+    q_deck_areas: Query<&DeckArea>,
+    mut writers: TableWriters<T>,
+    mut ew_command: MessageWriter<TableCommand<T>>,
+    sync: Option<Res<TableSync<T>>>,
+    mut ew_next: MessageWriter<NextPhase>,
+    mut ew_end: MessageWriter<EndTurn>,
+    mut waiting: Local<Option<((u32, Phase), Duration)>>,
+    mut cooldown: Local<u32>,
+    queue: Res<OperationQueue>,
+    time: Res<Time>,
+) where
+    T: Send + Clone + Sync + Debug + CardMetadata + 'static,
+{
+    // what the bot did is handled next frame, and phase changes only show up the frame after
+    if *cooldown > 0 {
+        *cooldown -= 1;
+        return;
+    }
+
+    let seats = &mut *seats;
+    let player = current.0;
+    let Some(ai) = seats.seats.get_mut(&player) else {
+        return;
+    };
+
+    // think only once the table has settled and the bot has waited its delay
+    let now = time.elapsed();
+    if !queue.is_idle(now) {
+        *waiting = None;
+        return;
+    }
+    let step = (turn.turn(), *phase.get());
+    let since = match *waiting {
+        Some((waiting_step, since)) if waiting_step == step => since,
+        _ => {
+            *waiting = Some((step, now));
+            now
+        }
+    };
+    if now - since < seats.think_delay {
+        return;
+    }
+
+    let view = AiView {
+        player,
+        phase: step.1,
+        hand: table
+            .hand(player)
+            .into_iter()
+            .filter_map(|(entity, data)| table.card_id(entity).map(|id| (id, data)))
+            .collect(),
+        table: q_play_areas
+            .iter()
+            .map(|area| {
+                let cards = table
+                    .play_area(area.marker, area.player)
+                    .into_iter()
+                    .filter_map(|(entity, data)| table.card_id(entity).map(|id| (id, data)))
+                    .collect();
+                ((area.marker, area.player), cards)
+            })
+            .collect(),
+        decks: q_deck_areas
+            .iter()
+            .map(|deck| (deck.marker, table.deck(deck.marker).len()))
+            .collect(),
+    };
+
+    let actions = ai.think(&view);
+    if actions.is_empty() {
+        ew_next.write(NextPhase);
+    }
+    for action in actions {
+        match action {
+            // synced tables order the command like one written by a player
+            AiAction::Table(command) if sync.is_some() => {
+                ew_command.write(command);
+            }
+            AiAction::Table(command) => apply(command, &table, &mut writers),
+            AiAction::NextPhase => {
+                ew_next.write(NextPhase);
+            }
+            AiAction::EndTurn => {
+                ew_end.write(EndTurn);
+            }
+        }
+    }
+
+    // wait again before the next thought, the table has to catch up with the actions
+    *waiting = Some((step, now));
+    *cooldown = 2;
+}
+
+/// Drives the seats in [`AiSeats`] on their turn. Needs the [`TurnPlugin`](crate::turn::TurnPlugin).
+/// With the [`SyncPlugin`](crate::sync::SyncPlugin), bots write [`TableCommand`]s like players do.
+pub struct AiPlugin<T>(PhantomData<T>);
+
+impl<T> Default for AiPlugin<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: Send + Clone + Sync + Debug + CardMetadata + 'static> Plugin for AiPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.add_message::<TableCommand<T>>()
+            .init_resource::<AiSeats<T>>()
+            .add_systems(Update, handle_ai_players::<T>);
+    }
+}
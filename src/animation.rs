@@ -0,0 +1,271 @@
+use bevy::prelude::*;
+use bevy_tweening::{lens::TransformPositionLens, Lens, Sequence, Tween, TweenAnim};
+use std::time::Duration;
+
+/// Timing of one kind of table animation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AnimationTiming {
+    /// Length of a single animation step.
+    pub duration: Duration,
+    /// Delay between consecutive cards moved by the same operation.
+    pub stagger: Duration,
+    pub ease: EaseFunction,
+}
+
+impl AnimationTiming {
+    pub const fn from_millis(duration: u64, stagger: u64) -> Self {
+        Self {
+            duration: Duration::from_millis(duration),
+            stagger: Duration::from_millis(stagger),
+            ease: EaseFunction::QuadraticIn,
+        }
+    }
+
+    pub fn with_ease(mut self, ease: EaseFunction) -> Self {
+        self.ease = ease;
+        self
+    }
+}
+
+/// Shape of the path a card flies along between two zones.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FlightPath {
+    /// Straight line, no lift.
+    Straight,
+    /// Quadratic bezier whose control point is lifted `lift` above the midpoint.
+    Quadratic { lift: f32 },
+    /// Cubic bezier whose control points are lifted `lift` above the start and the end, so the
+    /// card rises, travels and then drops onto its destination.
+    Cubic { lift: f32 },
+}
+
+impl FlightPath {
+    pub fn point(&self, start: Vec3, end: Vec3, t: f32) -> Vec3 {
+        let u = 1.0 - t;
+        match *self {
+            FlightPath::Straight => start.lerp(end, t),
+            FlightPath::Quadratic { lift } => {
+                let control = (start + end) / 2.0 + Vec3::Y * lift;
+                start * (u * u) + control * (2.0 * u * t) + end * (t * t)
+            }
+            FlightPath::Cubic { lift } => {
+                let control1 = start + Vec3::Y * lift;
+                let control2 = end + Vec3::Y * lift;
+                start * (u * u * u)
+                    + control1 * (3.0 * u * u * t)
+                    + control2 * (3.0 * u * t * t)
+                    + end * (t * t * t)
+            }
+        }
+    }
+}
+
+/// Moves a card along a [`FlightPath`] while rotating and scaling it, so the whole transform
+/// changes in a single tween.
+#[derive(Clone, Copy, Debug)]
+pub struct TransformFlightLens {
+    pub start: Transform,
+    pub end: Transform,
+    pub path: FlightPath,
+}
+
+impl Lens<Transform> for TransformFlightLens {
+    fn lerp(&mut self, mut target: Mut<Transform>, ratio: f32) {
+        target.translation = self
+            .path
+            .point(self.start.translation, self.end.translation, ratio);
+        target.rotation = self.start.rotation.slerp(self.end.rotation, ratio);
+        target.scale = self.start.scale.lerp(self.end.scale, ratio);
+    }
+}
+
+/// Durations, staggers and easing of every table animation.
+///
+/// Messages that carry a `timing` override replace the matching entry for that operation only.
+/// `speed` and `instant` apply on top of both.
+#[derive(Resource, Clone, Debug)]
+pub struct AnimationSettings {
+    pub shuffle: AnimationTiming,
+    pub reorder: AnimationTiming,
+    pub draw_to_hand: AnimationTiming,
+    pub draw_to_table: AnimationTiming,
+    pub deal: AnimationTiming,
+    pub place_on_table: AnimationTiming,
+    pub discard: AnimationTiming,
+    pub align: AnimationTiming,
+    pub hover: AnimationTiming,
+    pub peek: AnimationTiming,
+    pub select: AnimationTiming,
+    pub clear: AnimationTiming,
+    /// Path cards follow when they fly between zones.
+    pub flight_path: FlightPath,
+    /// Multiplier applied to every animation; 2.0 plays them twice as fast.
+    pub speed: f32,
+    /// Snap cards to their destination without tweening, e.g. to fast-forward or in tests.
+    pub instant: bool,
+}
+
+impl Default for AnimationSettings {
+    fn default() -> Self {
+        Self {
+            shuffle: AnimationTiming::from_millis(8, 8),
+            reorder: AnimationTiming::from_millis(150, 20),
+            draw_to_hand: AnimationTiming::from_millis(450, 150)
+                .with_ease(EaseFunction::CubicInOut),
+            draw_to_table: AnimationTiming::from_millis(450, 150)
+                .with_ease(EaseFunction::CubicInOut),
+            deal: AnimationTiming::from_millis(400, 120).with_ease(EaseFunction::CubicInOut),
+            place_on_table: AnimationTiming::from_millis(250, 0)
+                .with_ease(EaseFunction::CubicInOut),
+            discard: AnimationTiming::from_millis(300, 0).with_ease(EaseFunction::CubicInOut),
+            align: AnimationTiming::from_millis(75, 0),
+            hover: AnimationTiming::from_millis(100, 0),
+            peek: AnimationTiming::from_millis(300, 60).with_ease(EaseFunction::CubicInOut),
+            select: AnimationTiming::from_millis(100, 0),
+            clear: AnimationTiming::from_millis(150, 10),
+            flight_path: FlightPath::Quadratic { lift: 2.0 },
+            speed: 1.0,
+            instant: false,
+        }
+    }
+}
+
+impl AnimationSettings {
+    /// Resolve the timing of an operation: the message override if any, scaled by `speed`,
+    /// or zero when animations are instant.
+    pub fn resolve(
+        &self,
+        default: AnimationTiming,
+        timing: Option<AnimationTiming>,
+    ) -> AnimationTiming {
+        let timing = timing.unwrap_or(default);
+        if self.instant {
+            return AnimationTiming {
+                duration: Duration::ZERO,
+                stagger: Duration::ZERO,
+                ease: timing.ease,
+            };
+        }
+
+        let speed = self.speed.max(f32::EPSILON);
+        AnimationTiming {
+            duration: timing.duration.div_f32(speed),
+            stagger: timing.stagger.div_f32(speed),
+            ease: timing.ease,
+        }
+    }
+}
+
+/// Play `anim` on a card, or move it straight to `end` when animations are instant.
+pub(crate) fn animate(
+    commands: &mut Commands,
+    entity: Entity,
+    anim: TweenAnim,
+    end: Transform,
+    settings: &AnimationSettings,
+) {
+    if settings.instant {
+        commands.entity(entity).remove::<TweenAnim>().insert(end);
+    } else {
+        commands.entity(entity).insert(anim);
+    }
+}
+
+/// Wait `delay`, then fly from `start` to `end` in one tween of `timing.duration`.
+pub(crate) fn flight(
+    start: Transform,
+    end: Transform,
+    delay: Duration,
+    timing: &AnimationTiming,
+    path: FlightPath,
+) -> Sequence {
+    let idle_tween = Tween::new(
+        timing.ease,
+        delay,
+        TransformPositionLens {
+            start: start.translation,
+            end: start.translation,
+        },
+    );
+
+    let flight_tween = Tween::new(
+        timing.ease,
+        timing.duration,
+        TransformFlightLens { start, end, path },
+    );
+
+    idle_tween.then(flight_tween)
+}
+
+/// One card of a deck being put back onto the deck in a new order.
+pub(crate) struct Restack {
+    pub entity: Entity,
+    pub start: Transform,
+    /// Idle time before the card starts moving.
+    pub before: Duration,
+    /// Points the card passes through, one `timing.duration` step each.
+    pub path: Vec<Vec3>,
+    /// Idle time between the end of `path` and landing on the deck.
+    pub after: Duration,
+    /// Rotation to land with, when it differs from the starting one.
+    pub rotation: Option<Quat>,
+}
+
+/// Land `cards` on the deck at `deck_translation`, first card at the bottom, and return how
+/// long the whole restack takes together with where every card comes to rest.
+pub(crate) fn restack(
+    commands: &mut Commands,
+    cards: Vec<Restack>,
+    deck_translation: Vec3,
+    timing: &AnimationTiming,
+    settings: &AnimationSettings,
+) -> (Duration, Vec<(Entity, Transform)>) {
+    let step = |start: Vec3, end: Vec3, duration: Duration| {
+        Tween::new(timing.ease, duration, TransformPositionLens { start, end })
+    };
+
+    let mut total = Duration::ZERO;
+    let mut resting = Vec::with_capacity(cards.len());
+    for (i, card) in cards.into_iter().enumerate() {
+        let end = Vec3::new(deck_translation.x, i as f32 * 0.01, deck_translation.z);
+
+        let mut from = card.start.translation;
+        let mut steps = Vec::with_capacity(card.path.len() + 1);
+        for point in card.path.iter() {
+            steps.push(step(from, *point, timing.duration));
+            from = *point;
+        }
+        steps.push(step(from, from, card.after));
+
+        let landed = Transform {
+            translation: end,
+            rotation: card.rotation.unwrap_or(card.start.rotation),
+            scale: card.start.scale,
+        };
+        let landing = Tween::new(
+            timing.ease,
+            timing.duration,
+            TransformFlightLens {
+                start: card.start.with_translation(from),
+                end: landed,
+                path: FlightPath::Straight,
+            },
+        );
+
+        let mut steps = steps.into_iter();
+        let mut seq = step(card.start.translation, card.start.translation, card.before)
+            .then(steps.next().unwrap());
+        for tween in steps {
+            seq = seq.then(tween);
+        }
+        let seq = seq.then(landing);
+
+        animate(commands, card.entity, TweenAnim::new(seq), landed, settings);
+        resting.push((card.entity, landed));
+
+        let length = card.before + timing.duration * (card.path.len() as u32 + 1) + card.after;
+        total = total.max(length);
+    }
+
+    (total, resting)
+}
@@ -0,0 +1,270 @@
+use bevy::prelude::*;
+use bevy_tweening::TweenAnim;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt::Debug, time::Duration};
+
+use crate::animation::{animate, flight, AnimationSettings, AnimationTiming};
+use crate::events::LaMesaError;
+use crate::queue::{OperationQueue, Queued};
+use crate::{Card, CardIndex, CardOnTable, CardZone, Hand};
+
+/// How far every further attachment is shifted along the host, so the edge of each one
+/// stays visible.
+const ATTACH_SHIFT: f32 = 0.5;
+/// Height between the host and each attachment.
+const ATTACH_GAP: f32 = 0.005;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AttachSide {
+    /// Tucked under the host, sticking out above it.
+    #[default]
+    Under,
+    /// Laid over the host, leaving its top uncovered.
+    Over,
+}
+
+impl AttachSide {
+    /// Offset of the attachment in `slot` on this side, from 1, in the host's own space.
+    fn offset(self, slot: usize) -> Vec3 {
+        let n = slot as f32;
+        match self {
+            AttachSide::Under => Vec3::new(0.0, -ATTACH_GAP * n, -ATTACH_SHIFT * n),
+            AttachSide::Over => Vec3::new(0.0, ATTACH_GAP * n, ATTACH_SHIFT * n),
+        }
+    }
+}
+
+/// Attach a card from hand or from the table to `host_entity`, which has to be on a
+/// [`PlayArea`](crate::PlayArea). The card joins the host's play area and follows the host
+/// while it moves around the table.
+#[derive(Message, Clone)]
+pub struct AttachCard {
+    pub card_entity: Entity,
+    pub host_entity: Entity,
+    pub side: AttachSide,
+    pub timing: Option<AnimationTiming>,
+}
+
+/// Detach a card from its host, leaving it where it is.
+#[derive(Message, Clone)]
+pub struct DetachCard {
+    pub card_entity: Entity,
+}
+
+/// Card attached to `host`. Removed when either card leaves the table, or when the card is
+/// moved on its own.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Attached {
+    pub host: Entity,
+    pub side: AttachSide,
+    /// Place among the host's attachments on `side`, from 1; a slot left by a detached card
+    /// is taken by the next card attached on that side.
+    pub slot: usize,
+    /// Position relative to the host, in the host's own space.
+    pub offset: Vec3,
+    zone: CardZone,
+}
+
+pub fn handle_attach_card<T>(
+    mut commands: Commands,
+    mut er_attach: MessageReader<AttachCard>,
+    mut card_index: ResMut<CardIndex<T>>,
+    q_cards: Query<(&Card<T>, &Transform)>,
+    q_attached: Query<&Attached>,
+    mut ew_error: MessageWriter<LaMesaError>,
+    mut queue: ResMut<OperationQueue>,
+    mut pending: Local<Vec<Queued<AttachCard>>>,
+    time: Res<Time>,
+    settings: Res<AnimationSettings>,
+) where
+    T: Send + Clone + Sync + Debug + 'static,
+{
+    let now = time.elapsed();
+    for event in er_attach.read() {
+        let mut zones: Vec<CardZone> = card_index.zone_of(event.host_entity).into_iter().collect();
+        zones.extend(card_index.zone_of(event.card_entity));
+        pending.push(queue.enqueue(zones, event.clone()));
+    }
+
+    // attachments made this frame are not in `q_attached` yet
+    let mut taken: HashMap<(Entity, AttachSide), Vec<usize>> = HashMap::new();
+
+    while let Some(Queued {
+        message: event,
+        zones,
+        ..
+    }) = queue.next_ready(&mut pending, now)
+    {
+        let (Ok((card, start)), Ok((_, host))) = (
+            q_cards.get(event.card_entity),
+            q_cards.get(event.host_entity),
+        ) else {
+            let card_entity = if q_cards.contains(event.card_entity) {
+                event.host_entity
+            } else {
+                event.card_entity
+            };
+            ew_error.write(LaMesaError::UnknownCard { card_entity });
+            continue;
+        };
+        let (start, host) = (*start, *host);
+
+        let zone = card_index.zone_of(event.card_entity);
+        if !matches!(zone, Some(CardZone::Hand { .. } | CardZone::Table { .. })) {
+            ew_error.write(LaMesaError::CardNotInZone {
+                card_entity: event.card_entity,
+                zone,
+            });
+            continue;
+        }
+        let host_zone = card_index.zone_of(event.host_entity);
+        let Some(CardZone::Table { marker, player }) = host_zone else {
+            ew_error.write(LaMesaError::CardNotInZone {
+                card_entity: event.host_entity,
+                zone: host_zone,
+            });
+            continue;
+        };
+
+        // one level only: hosts are not attached and attachments host nothing
+        if event.card_entity == event.host_entity
+            || q_attached.contains(event.host_entity)
+            || q_attached
+                .iter()
+                .any(|attached| attached.host == event.card_entity)
+        {
+            ew_error.write(LaMesaError::CannotAttach {
+                card_entity: event.card_entity,
+                host_entity: event.host_entity,
+            });
+            continue;
+        }
+
+        let slots = taken
+            .entry((event.host_entity, event.side))
+            .or_insert_with(|| {
+                q_attached
+                    .iter()
+                    .filter(|attached| {
+                        attached.host == event.host_entity && attached.side == event.side
+                    })
+                    .map(|attached| attached.slot)
+                    .collect()
+            });
+        let slot = (1..).find(|slot| !slots.contains(slot)).unwrap();
+        slots.push(slot);
+        let offset = event.side.offset(slot);
+
+        let end = Transform {
+            translation: host.translation + host.rotation * offset,
+            rotation: host.rotation,
+            scale: start.scale,
+        };
+
+        let timing = settings.resolve(settings.place_on_table, event.timing);
+        let seq = flight(start, end, Duration::ZERO, &timing, settings.flight_path);
+
+        let card = Card::<T> {
+            pickable: card.pickable,
+            transform: Some(end),
+            data: card.data.clone(),
+        };
+
+        let zone = CardZone::Table { marker, player };
+        card_index.set_zone(event.card_entity, Some(zone));
+
+        commands.entity(event.card_entity).remove::<Hand>().insert((
+            card,
+            CardOnTable { marker, player },
+            Attached {
+                host: event.host_entity,
+                side: event.side,
+                slot,
+                offset,
+                zone,
+            },
+        ));
+        animate(
+            &mut commands,
+            event.card_entity,
+            TweenAnim::new(seq),
+            end,
+            &settings,
+        );
+
+        queue.hold(&zones, now + timing.duration);
+    }
+}
+
+pub fn handle_detach_card(
+    mut commands: Commands,
+    mut er_detach: MessageReader<DetachCard>,
+    q_attached: Query<(), With<Attached>>,
+    mut ew_error: MessageWriter<LaMesaError>,
+) {
+    for event in er_detach.read() {
+        if q_attached.contains(event.card_entity) {
+            commands.entity(event.card_entity).remove::<Attached>();
+        } else {
+            ew_error.write(LaMesaError::NotAttached {
+                card_entity: event.card_entity,
+            });
+        }
+    }
+}
+
+/// Keep attached cards on their host, runs before transforms are propagated so they move in
+/// the same frame as the host.
+pub fn handle_follow_host<T>(
+    mut commands: Commands,
+    mut card_index: ResMut<CardIndex<T>>,
+    mut q_attached: Query<(Entity, &mut Attached)>,
+    mut q_cards: Query<(&mut Card<T>, &mut Transform)>,
+) where
+    T: Send + Clone + Sync + Debug + 'static,
+{
+    for (entity, mut attached) in q_attached.iter_mut() {
+        // the card was moved to another zone on its own, or the host left the table; placing
+        // it on its own play area detaches it when it is placed
+        let host_zone = card_index.zone_of(attached.host);
+        if card_index.zone_of(entity) != Some(attached.zone)
+            || !matches!(host_zone, Some(CardZone::Table { .. }))
+        {
+            commands.entity(entity).remove::<Attached>();
+            continue;
+        }
+
+        // the host went to another play area and takes the card along
+        if let Some(zone @ CardZone::Table { marker, player }) = host_zone {
+            if zone != attached.zone {
+                attached.zone = zone;
+                card_index.set_zone(entity, Some(zone));
+                commands
+                    .entity(entity)
+                    .insert(CardOnTable { marker, player });
+            }
+        }
+
+        let Ok((host_card, host)) = q_cards.get_mut(attached.host) else {
+            continue;
+        };
+        // every move of the host, even within its play area, changes one of the two
+        if !host.is_changed() && !host_card.is_changed() {
+            continue;
+        }
+        let (host_card, host) = (host_card.transform, *host);
+
+        if let Ok((mut card, mut transform)) = q_cards.get_mut(entity) {
+            transform.translation = host.translation + host.rotation * attached.offset;
+            transform.rotation = host.rotation;
+            // the card rests where the host rests, so hover and selection start from there
+            if let Some(host_resting) = host_card {
+                card.transform = Some(Transform {
+                    translation: host_resting.translation + host_resting.rotation * attached.offset,
+                    rotation: host_resting.rotation,
+                    scale: transform.scale,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use bevy::prelude::*;
+use std::collections::{BTreeMap, HashSet};
+
+/// Named integer counters on a card, e.g. damage or +1/+1 markers. A counter that drops to
+/// zero is removed; the others are listed in a [`CounterBadge`] next to the card.
+#[derive(Component, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Counters(BTreeMap<String, i32>);
+
+impl Counters {
+    pub fn get(&self, name: &str) -> i32 {
+        self.0.get(name).copied().unwrap_or(0)
+    }
+
+    pub fn set(&mut self, name: impl Into<String>, value: i32) {
+        let name = name.into();
+        if value == 0 {
+            self.0.remove(&name);
+        } else {
+            self.0.insert(name, value);
+        }
+    }
+
+    /// Add `amount`, which may be negative, to a counter and return its new value.
+    pub fn add(&mut self, name: impl Into<String>, amount: i32) -> i32 {
+        let name = name.into();
+        let value = self.get(&name) + amount;
+        self.set(name, value);
+        value
+    }
+
+    /// Counters sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, i32)> {
+        self.0.iter().map(|(name, value)| (name.as_str(), *value))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct BadgeSettings {
+    /// Point of the card the badge is pinned to, in the card's own space.
+    pub anchor: Vec3,
+    pub font_size: f32,
+    pub text_color: Color,
+    pub background: Color,
+}
+
+impl Default for BadgeSettings {
+    fn default() -> Self {
+        Self {
+            anchor: Vec3::new(1.25, 0.0, -1.75),
+            font_size: 14.0,
+            text_color: Color::WHITE,
+            background: Color::srgba(0.0, 0.0, 0.0, 0.75),
+        }
+    }
+}
+
+/// UI node listing the [`Counters`] of `card`, kept over the card on screen.
+#[derive(Component)]
+pub struct CounterBadge {
+    pub card: Entity,
+}
+
+pub fn handle_counter_badges(
+    mut commands: Commands,
+    q_changed: Query<(Entity, &Counters), Changed<Counters>>,
+    mut removed_counters: RemovedComponents<Counters>,
+    mut q_badges: Query<(Entity, &CounterBadge, &mut Node, &mut Visibility)>,
+    q_cards: Query<&GlobalTransform, With<Counters>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    settings: Res<BadgeSettings>,
+) {
+    // badges are rebuilt whenever the counters change, and dropped with the counters
+    let mut stale: HashSet<Entity> = removed_counters.read().collect();
+    stale.extend(q_changed.iter().map(|(entity, _)| entity));
+    for (badge, CounterBadge { card }, _, _) in q_badges.iter() {
+        if stale.contains(card) {
+            commands.entity(badge).despawn();
+        }
+    }
+
+    for (card, counters) in q_changed.iter().filter(|(_, c)| !c.is_empty()) {
+        commands
+            .spawn((
+                Name::new("CounterBadge"),
+                CounterBadge { card },
+                Node {
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(4.0)),
+                    ..default()
+                },
+                BackgroundColor(settings.background),
+                // shown once it has been placed over the card
+                Visibility::Hidden,
+            ))
+            .with_children(|parent| {
+                for (name, value) in counters.iter() {
+                    parent.spawn((
+                        Text::new(format!("{name} {value}")),
+                        TextFont {
+                            font_size: settings.font_size,
+                            ..default()
+                        },
+                        TextColor(settings.text_color),
+                    ));
+                }
+            });
+    }
+
+    let camera = q_camera.single().ok();
+    for (_, badge, mut node, mut visibility) in q_badges.iter_mut() {
+        let position = camera.zip(q_cards.get(badge.card).ok()).and_then(
+            |((camera, camera_transform), card_transform)| {
+                camera
+                    .world_to_viewport(
+                        camera_transform,
+                        card_transform.transform_point(settings.anchor),
+                    )
+                    .ok()
+            },
+        );
+
+        match position {
+            Some(position) => {
+                node.left = Val::Px(position.x);
+                node.top = Val::Px(position.y);
+                visibility.set_if_neq(Visibility::Inherited);
+            }
+            None => {
+                visibility.set_if_neq(Visibility::Hidden);
+            }
+        }
+    }
+}
@@ -1,18 +1,32 @@
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy_defer::*;
 use bevy_tweening::{lens::*, *};
+use rand::{rngs::StdRng, SeedableRng};
 
-use rand::prelude::*;
-use std::fmt::Debug;
-use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, fmt::Debug, time::Duration};
 
+use crate::animation::{
+    animate, flight, restack, AnimationSettings, AnimationTiming, FlightPath, TransformFlightLens,
+};
+use crate::attach::Attached;
+use crate::inspect::{on_card_pressed, LongPress};
+use crate::peek::{cancel_peek, on_peeked_card_drop, Peeking};
+use crate::queue::{OperationQueue, Queued};
+use crate::rules::RulesCheck;
+use crate::selection::{raise, Selected, SelectionSettings};
+use crate::shuffle::{reorder, ReorderOp, ShuffleStyle, TableRng};
 use crate::{
-    Card, CardMetadata, CardOnTable, Deck, DeckArea, Hand, HandArea, PlayArea, DECK_WIDTH,
+    Card, CardId, CardIndex, CardMetadata, CardOnTable, CardZone, Deck, DeckArea, Despawning, Hand,
+    HandArea, HoverStyle, Hovered, PlayArea, DECK_WIDTH,
 };
+#[cfg(feature = "render")]
+use crate::{CardBack, CardCover, CardFace};
 
 // Events
 
-#[derive(Message)]
+#[derive(Message, Clone)]
 pub struct RenderDeck<T: Send + Clone + Sync + Debug + CardMetadata + 'static> {
     pub deck_entity: Entity,
     pub deck: Vec<T>,
@@ -21,45 +35,246 @@ pub struct RenderDeck<T: Send + Clone + Sync + Debug + CardMetadata + 'static> {
 #[derive(Message)]
 pub struct DeckRendered {}
 
-#[derive(Message)]
+/// Despawn every card of a deck, optionally flying them off the table first.
+#[derive(Message, Clone)]
+pub struct ClearDeck {
+    pub deck_entity: Entity,
+    pub animate: bool,
+}
+
+/// Despawn every card regardless of the zone it is in, once the operations already queued
+/// on the table are over.
+#[derive(Message, Clone)]
+pub struct DespawnAllCards {
+    pub animate: bool,
+}
+
+/// Replace the contents of a deck in one go: old cards are despawned and the new ones rendered
+/// in the same frame, after the operations already queued on the deck.
+#[derive(Message, Clone)]
+pub struct ReplaceDeck<T: Send + Clone + Sync + Debug + CardMetadata + 'static> {
+    pub deck_entity: Entity,
+    pub deck: Vec<T>,
+}
+
+/// Reorder a deck without randomness: cut, reverse, sort or bring a card to the top.
+#[derive(Message, Clone)]
+pub struct ReorderDeck<T: Send + Clone + Sync + Debug + CardMetadata + 'static> {
+    pub deck_entity: Entity,
+    pub op: ReorderOp<T>,
+    pub timing: Option<AnimationTiming>,
+}
+
+/// Order a deck is left in once a [`ReorderDeck`] on it has been handled, top card first; the
+/// order it already had when the reorder named a card that is not in the deck.
+#[derive(Message, Clone, Debug)]
+pub struct DeckReordered {
+    pub deck_entity: Entity,
+    pub cards: Vec<Entity>,
+}
+
+#[derive(Message, Clone)]
 pub struct DeckShuffle {
     pub deck_entity: Entity,
-    pub duration: u64,
+    pub style: ShuffleStyle,
+    /// Roll the shuffle from this seed instead of the [`TableRng`], so tables given the same
+    /// seed shuffle the same deck alike.
+    pub seed: Option<u64>,
+    pub timing: Option<AnimationTiming>,
 }
 
-#[derive(Message)]
+/// Order a deck was shuffled into, top card first, written when the shuffle starts.
+#[derive(Message, Clone, Debug)]
+pub struct DeckShuffled {
+    pub deck_entity: Entity,
+    pub cards: Vec<Entity>,
+}
+
+/// Replace the data of a card, and its face when the table is rendered, e.g. once the player
+/// learns what a hidden card is.
+#[derive(Message, Clone)]
+pub struct RevealCard<T: Send + Clone + Sync + Debug + CardMetadata + 'static> {
+    pub card_entity: Entity,
+    pub data: T,
+}
+
+#[derive(Message, Clone)]
 pub struct AlignCardsInHand {
     pub player: usize,
+    pub timing: Option<AnimationTiming>,
 }
 
-#[derive(Message)]
+#[derive(Message, Clone)]
 pub struct PlaceCardOnTable {
     pub card_entity: Entity,
     pub marker: usize,
     pub player: usize,
+    pub timing: Option<AnimationTiming>,
 }
 
-#[derive(Message)]
+#[derive(Message, Clone)]
 pub struct DiscardCardToDeck {
     pub card_entity: Entity,
     pub deck_entity: Entity,
+    pub timing: Option<AnimationTiming>,
 }
 
-#[derive(Message)]
+#[derive(Message, Clone)]
 pub struct DrawToHand {
     pub deck_entity: Entity,
     pub num_cards: usize,
     pub player: usize,
+    pub timing: Option<AnimationTiming>,
 }
 
-#[derive(Message)]
+/// Order in which [`Deal`] hands out cards.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DealOrder {
+    /// One card to every player in turn, `cards_each` times around the table.
+    #[default]
+    RoundRobin,
+    /// All `cards_each` cards to the first player, then to the next one.
+    Batch,
+}
+
+/// Deal `cards_each` cards from a deck to every player in `players`, one card at a time.
+#[derive(Message, Clone)]
+pub struct Deal {
+    pub deck_entity: Entity,
+    pub players: Vec<usize>,
+    pub cards_each: usize,
+    pub order: DealOrder,
+    pub timing: Option<AnimationTiming>,
+}
+
+#[derive(Message, Clone)]
 pub struct DrawToTable {
     pub deck_entity: Entity,
     pub play_area_markers: Vec<usize>,
     pub player: usize,
-    pub duration: u64,
+    pub timing: Option<AnimationTiming>,
+}
+
+/// Emitted instead of panicking when an operation refers to something that does not exist or
+/// to a card that is not where the operation expects it.
+#[derive(Message, Clone, Debug, PartialEq)]
+pub enum LaMesaError {
+    UnknownDeck {
+        deck_entity: Entity,
+    },
+    UnknownCard {
+        card_entity: Entity,
+    },
+    UnknownHand {
+        player: usize,
+    },
+    UnknownPlayArea {
+        marker: usize,
+        player: usize,
+    },
+    /// The card is in `zone`, which the operation does not accept.
+    CardNotInZone {
+        card_entity: Entity,
+        zone: Option<CardZone>,
+    },
+    /// [`CardRules::can_play`](crate::rules::CardRules::can_play) refused the move.
+    PlayRefused {
+        card_entity: Entity,
+        marker: usize,
+        player: usize,
+    },
+    /// [`CardRules::can_draw`](crate::rules::CardRules::can_draw) refused the move.
+    DrawRefused {
+        deck_entity: Entity,
+        player: usize,
+    },
+    /// [`CardRules::can_discard`](crate::rules::CardRules::can_discard) refused the move.
+    DiscardRefused {
+        card_entity: Entity,
+        deck_entity: Entity,
+    },
+    /// Nobody is peeking at the top of this deck.
+    NotPeeking {
+        deck_entity: Entity,
+    },
+    /// No deck area has this marker.
+    UnknownDeckMarker {
+        marker: usize,
+    },
+    /// No rendered card has this id.
+    UnknownCardId {
+        card_id: CardId,
+    },
+    /// The host is the card itself, is attached to another card, or the card hosts others.
+    CannotAttach {
+        card_entity: Entity,
+        host_entity: Entity,
+    },
+    NotAttached {
+        card_entity: Entity,
+    },
+    /// The card is listed more than once where every card may appear only once.
+    DuplicateCard {
+        card_entity: Entity,
+    },
+}
+
+impl std::fmt::Display for LaMesaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LaMesaError::UnknownDeck { deck_entity } => {
+                write!(f, "{deck_entity} is not a deck area")
+            }
+            LaMesaError::UnknownCard { card_entity } => write!(f, "{card_entity} is not a card"),
+            LaMesaError::UnknownHand { player } => {
+                write!(f, "no hand area for player {player}")
+            }
+            LaMesaError::UnknownPlayArea { marker, player } => {
+                write!(f, "no play area {marker} for player {player}")
+            }
+            LaMesaError::CardNotInZone { card_entity, zone } => {
+                write!(f, "card {card_entity} cannot be used from zone {zone:?}")
+            }
+            LaMesaError::PlayRefused {
+                card_entity,
+                marker,
+                player,
+            } => write!(
+                f,
+                "card {card_entity} may not be played on play area {marker} of player {player}"
+            ),
+            LaMesaError::DrawRefused {
+                deck_entity,
+                player,
+            } => write!(f, "player {player} may not draw from deck {deck_entity}"),
+            LaMesaError::DiscardRefused {
+                card_entity,
+                deck_entity,
+            } => write!(
+                f,
+                "card {card_entity} may not be discarded to deck {deck_entity}"
+            ),
+            LaMesaError::NotPeeking { deck_entity } => {
+                write!(f, "nobody is peeking at deck {deck_entity}")
+            }
+            LaMesaError::UnknownDeckMarker { marker } => write!(f, "no deck area {marker}"),
+            LaMesaError::UnknownCardId { card_id } => write!(f, "no card with id {}", card_id.0),
+            LaMesaError::CannotAttach {
+                card_entity,
+                host_entity,
+            } => write!(f, "card {card_entity} cannot be attached to {host_entity}"),
+            LaMesaError::NotAttached { card_entity } => {
+                write!(f, "card {card_entity} is not attached")
+            }
+            LaMesaError::DuplicateCard { card_entity } => {
+                write!(f, "card {card_entity} is listed more than once")
+            }
+        }
+    }
 }
 
+impl std::error::Error for LaMesaError {}
+
 #[derive(Message)]
 pub struct CardHover {
     pub entity: Entity,
@@ -99,257 +314,688 @@ impl From<Pointer<Press>> for CardPress {
     }
 }
 
+/// Meshes, materials and images of the cards. Missing when the plugin runs headless, in which
+/// case cards are spawned without any.
+#[cfg(feature = "render")]
+#[derive(SystemParam)]
+pub struct CardAssets<'w> {
+    meshes: Option<ResMut<'w, Assets<Mesh>>>,
+    materials: Option<ResMut<'w, Assets<StandardMaterial>>>,
+    asset_server: Option<Res<'w, AssetServer>>,
+}
+
+/// Without the `render` feature cards are never drawn, so they get no meshes.
+#[cfg(not(feature = "render"))]
+#[derive(SystemParam)]
+pub struct CardAssets<'w> {
+    meshes: Option<ResMut<'w, Assets<Mesh>>>,
+}
+
+#[cfg(feature = "render")]
+impl CardAssets<'_> {
+    /// Give a card its meshes: the card itself, its face, its back and the cover hiding the
+    /// face. Headless cards have none.
+    fn dress<T: CardMetadata>(&mut self, commands: &mut Commands, card_entity: Entity, card: &T) {
+        if let (Some(meshes), Some(materials), Some(asset_server)) = (
+            self.meshes.as_mut(),
+            self.materials.as_mut(),
+            self.asset_server.as_ref(),
+        ) {
+            let back_texture = asset_server.load(card.back_image_filename());
+            let back_material = materials.add(StandardMaterial {
+                base_color_texture: Some(back_texture.clone()),
+                ..Default::default()
+            });
+
+            let face_texture = asset_server.load(card.front_image_filename());
+            let face_material: Handle<StandardMaterial> = materials.add(StandardMaterial {
+                base_color_texture: Some(face_texture.clone()),
+                ..Default::default()
+            });
+
+            commands
+                .entity(card_entity)
+                .insert(Mesh3d(
+                    meshes.add(Plane3d::default().mesh().size(2.5, 3.5).subdivisions(10)),
+                ))
+                .with_children(|parent| {
+                    // face
+                    parent.spawn((
+                        CardFace,
+                        Mesh3d(
+                            meshes.add(Plane3d::default().mesh().size(2.5, 3.5).subdivisions(10)),
+                        ),
+                        MeshMaterial3d(face_material),
+                    ));
+
+                    // back
+                    parent.spawn((
+                        CardBack,
+                        Mesh3d(
+                            meshes.add(Plane3d::default().mesh().size(2.5, 3.5).subdivisions(10)),
+                        ),
+                        MeshMaterial3d(back_material.clone()),
+                        Transform::IDENTITY
+                            .with_rotation(Quat::from_rotation_z(std::f32::consts::PI)),
+                    ));
+
+                    // back drawn over the face while it is hidden from the local player
+                    parent.spawn((
+                        CardCover,
+                        Mesh3d(
+                            meshes.add(Plane3d::default().mesh().size(2.5, 3.5).subdivisions(10)),
+                        ),
+                        MeshMaterial3d(back_material),
+                        Visibility::Hidden,
+                    ));
+                });
+        }
+    }
+
+    /// Free the mesh and material of a part of a card.
+    fn free(&mut self, q_card_parts: &CardParts, part: Entity) {
+        if let Ok((mesh, material)) = q_card_parts.get(part) {
+            if let Some(meshes) = self.meshes.as_mut() {
+                meshes.remove(mesh);
+            }
+            if let (Some(materials), Some(material)) = (self.materials.as_mut(), material) {
+                materials.remove(material);
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "render"))]
+impl CardAssets<'_> {
+    fn dress<T: CardMetadata>(&mut self, _: &mut Commands, _: Entity, _: &T) {}
+
+    fn free(&mut self, q_card_parts: &CardParts, part: Entity) {
+        if let (Ok(mesh), Some(meshes)) = (q_card_parts.get(part), self.meshes.as_mut()) {
+            meshes.remove(mesh);
+        }
+    }
+}
+
+/// Meshes and materials a card is drawn with, freed when it is despawned.
+#[cfg(feature = "render")]
+type CardParts<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Mesh3d,
+        Option<&'static MeshMaterial3d<StandardMaterial>>,
+    ),
+>;
+#[cfg(not(feature = "render"))]
+type CardParts<'w, 's> = Query<'w, 's, &'static Mesh3d>;
+
 // Event Handlers
 pub fn handle_card_hover<T>(
     mut commands: Commands,
     mut hover: MessageReader<CardHover>,
-    mut cards_in_hand: Query<(Entity, &mut Card<T>, &Hand, &mut Transform)>,
+    q_cards: Query<(&Card<T>, &Transform, Option<&Selected>, Option<&Hovered>)>,
+    q_deck_cards: Query<(Entity, &Transform, &Deck), With<Card<T>>>,
+    q_hand_areas: Query<(&HandArea, Option<&HoverStyle>)>,
+    q_play_areas: Query<(&PlayArea, Option<&HoverStyle>)>,
+    q_deck_areas: Query<(&DeckArea, Option<&HoverStyle>)>,
+    q_camera: Query<&GlobalTransform, With<Camera3d>>,
+    card_index: Res<CardIndex<T>>,
+    queue: Res<OperationQueue>,
+    time: Res<Time>,
+    settings: Res<AnimationSettings>,
+    selection_settings: Res<SelectionSettings>,
 ) where
     T: Send + Sync + Debug + 'static,
 {
-    hover.read().for_each(|hover| {
-        if let Ok((_, card, hand, _transform)) = cards_in_hand.get_mut(hover.entity) {
-            if card.pickable && card.transform.is_some() {
-                let start_translation = card.transform.unwrap().translation;
-                let tween = Tween::new(
-                    EaseFunction::QuadraticIn,
-                    Duration::from_millis(100),
-                    TransformPositionLens {
-                        start: start_translation,
-                        end: start_translation
-                            + match hand.player {
-                                1 => Vec3::new(0., 0.7 / 3.0, 0.7 / 3.0),
-                                _ => Vec3::new(0., 0.7 / 3.0, 0.0),
-                            },
-                    },
-                );
+    let timing = settings.resolve(settings.hover, None);
+    let now = time.elapsed();
+
+    for hover in hover.read() {
+        let Ok((card, transform, selected, hovered)) = q_cards.get(hover.entity) else {
+            continue;
+        };
+        let Some(zone) = card_index.zone_of(hover.entity) else {
+            continue;
+        };
+        if queue.is_pending(zone, now) {
+            continue;
+        }
 
-                commands.entity(hover.entity).insert(TweenAnim::new(tween));
+        // rotation and scale are kept from before the previous hover, if it is still running
+        let mut rest = hovered.map_or(*transform, |hovered| hovered.rest);
+        if let CardZone::Deck { marker } = zone {
+            // only the top card of a deck reacts
+            let top = q_deck_cards
+                .iter()
+                .filter(|(_, _, deck)| deck.marker == marker)
+                .max_by(|a, b| a.1.translation.y.partial_cmp(&b.1.translation.y).unwrap())
+                .map(|(entity, _, _)| entity);
+            if top != Some(hover.entity) {
+                continue;
             }
+        } else {
+            let Some(resting) = card.transform.filter(|_| card.pickable) else {
+                continue;
+            };
+            rest.translation = resting.translation + raise(selected, &selection_settings);
         }
-    });
+
+        let style = hover_style(zone, &q_hand_areas, &q_play_areas, &q_deck_areas);
+        let end = match style {
+            HoverStyle::None => continue,
+            HoverStyle::Lift(offset) => rest.with_translation(rest.translation + offset),
+            HoverStyle::Scale(factor) => rest.with_scale(rest.scale * factor),
+            HoverStyle::Tilt(amount) => {
+                let Ok(camera) = q_camera.single() else {
+                    continue;
+                };
+                let to_camera = (camera.translation() - rest.translation).normalize_or_zero();
+                if to_camera == Vec3::ZERO {
+                    continue;
+                }
+
+                // turn whichever side of the card faces up towards the camera
+                let mut normal = rest.rotation * Vec3::Y;
+                if normal.dot(to_camera) < 0.0 {
+                    normal = -normal;
+                }
+                let turn = Quat::IDENTITY.slerp(
+                    Quat::from_rotation_arc(normal, to_camera),
+                    amount.clamp(0.0, 1.0),
+                );
+                rest.with_rotation(turn * rest.rotation)
+            }
+        };
+
+        let tween = Tween::new(
+            timing.ease,
+            timing.duration,
+            TransformFlightLens {
+                start: *transform,
+                end,
+                path: FlightPath::Straight,
+            },
+        );
+
+        commands.entity(hover.entity).insert(Hovered { zone, rest });
+        animate(
+            &mut commands,
+            hover.entity,
+            TweenAnim::new(tween),
+            end,
+            &settings,
+        );
+    }
 }
 
 pub fn handle_card_out<T>(
     mut commands: Commands,
     mut out: MessageReader<CardOut>,
-    mut query: Query<(Entity, &Card<T>, &Hand, &mut Transform)>,
+    q_cards: Query<(&Card<T>, &Transform, Option<&Selected>, &Hovered)>,
+    card_index: Res<CardIndex<T>>,
+    queue: Res<OperationQueue>,
+    time: Res<Time>,
+    settings: Res<AnimationSettings>,
+    selection_settings: Res<SelectionSettings>,
 ) where
     T: Send + Sync + Debug + 'static,
 {
-    out.read().for_each(|hover| {
-        if let Ok((_, card, _, transform)) = query.get_mut(hover.entity) {
-            if card.pickable && card.transform.is_some() {
-                let tween = Tween::new(
-                    EaseFunction::QuadraticIn,
-                    Duration::from_millis(100),
-                    TransformPositionLens {
-                        start: transform.translation,
-                        end: card.transform.unwrap().translation,
-                    },
-                );
+    let timing = settings.resolve(settings.hover, None);
+    let now = time.elapsed();
+
+    for out in out.read() {
+        let Ok((card, transform, selected, hovered)) = q_cards.get(out.entity) else {
+            continue;
+        };
+        commands.entity(out.entity).remove::<Hovered>();
+
+        // an operation moved the card in the meantime and owns its transform now
+        if card_index.zone_of(out.entity) != Some(hovered.zone)
+            || queue.is_pending(hovered.zone, now)
+        {
+            continue;
+        }
 
-                commands.entity(hover.entity).insert(TweenAnim::new(tween));
+        let mut end = hovered.rest;
+        if !matches!(hovered.zone, CardZone::Deck { .. }) {
+            if let Some(resting) = card.transform {
+                end.translation = resting.translation + raise(selected, &selection_settings);
             }
         }
-    });
+
+        let tween = Tween::new(
+            timing.ease,
+            timing.duration,
+            TransformFlightLens {
+                start: *transform,
+                end,
+                path: FlightPath::Straight,
+            },
+        );
+
+        animate(
+            &mut commands,
+            out.entity,
+            TweenAnim::new(tween),
+            end,
+            &settings,
+        );
+    }
 }
 
 pub fn handle_deck_shuffle<T>(
     mut commands: Commands,
     mut shuffle: MessageReader<DeckShuffle>,
-    query_cards: Query<(Entity, &Card<T>, &mut Transform, &Deck)>,
+    mut query_cards: Query<(Entity, &mut Card<T>, &Transform, &Deck)>,
     query_deck: Query<(Entity, &Transform, &DeckArea), Without<Deck>>,
+    deck_areas: Query<&DeckArea>,
+    mut ew_error: MessageWriter<LaMesaError>,
+    mut ew_shuffled: MessageWriter<DeckShuffled>,
+    mut queue: ResMut<OperationQueue>,
+    mut pending: Local<Vec<Queued<DeckShuffle>>>,
+    mut rng: ResMut<TableRng>,
+    time: Res<Time>,
+    settings: Res<AnimationSettings>,
 ) where
     T: Send + Clone + Sync + Debug + 'static,
 {
-    shuffle.read().for_each(|shuffle| {
-        let shuffle_deck = query_deck.get(shuffle.deck_entity).unwrap().2;
+    let now = time.elapsed();
+    for shuffle in shuffle.read() {
+        let zones = deck_zones(&deck_areas, shuffle.deck_entity);
+        pending.push(queue.enqueue(zones, shuffle.clone()));
+    }
 
-        // list all cards whose parent is deck
-        let cards: Vec<(Entity, &Card<T>, &Transform)> = query_cards
+    while let Some(Queued {
+        message: shuffle,
+        zones,
+        ..
+    }) = queue.next_ready(&mut pending, now)
+    {
+        let Ok((_, shuffle_deck_transform, shuffle_deck)) = query_deck.get(shuffle.deck_entity)
+        else {
+            ew_error.write(LaMesaError::UnknownDeck {
+                deck_entity: shuffle.deck_entity,
+            });
+            continue;
+        };
+
+        // list all cards whose parent is deck, bottom card first
+        let mut cards: Vec<(Entity, Transform)> = query_cards
             .iter()
             .filter(|(_, _, _, deck)| deck.marker == shuffle_deck.marker)
-            .map(|(entity, card, transform, _)| (entity, card, transform))
+            .map(|(entity, _, transform, _)| (entity, *transform))
             .collect();
+        cards.sort_by(|a, b| a.1.translation.y.partial_cmp(&b.1.translation.y).unwrap());
 
-        // shuffle the cards
-        let mut rng = rand::thread_rng();
-        let mut shuffled = cards.clone();
-        shuffled.shuffle(&mut rng);
-
-        // once cards shuffled reorder them with animation
-        let duration = shuffle.duration;
-        let random_offset_right = Vec3::new(0.0, 0.0, -2.6);
-        let random_offset_left = Vec3::new(0.0, 0.0, 2.6);
-
-        // find deck with deck number
-        let mut deck_translation = query_deck
-            .iter()
-            .find(|(_, _, deck)| deck.marker == shuffle_deck.marker)
-            .unwrap()
-            .1
-            .translation;
+        let timing = settings.resolve(settings.shuffle, shuffle.timing);
+        let mut deck_translation = shuffle_deck_transform.translation;
         deck_translation.y = 0.0;
 
-        for (i, (entity, _, transform)) in shuffled.iter().enumerate() {
-            // choose random 3 to the left or 3 to the right
-            let random_offset = if i % 2 == 0 {
-                random_offset_right
-            } else {
-                random_offset_left
-            };
+        // shuffle the cards, then reorder them with animation
+        let plan = match shuffle.seed {
+            Some(seed) => shuffle.style.plan(
+                &cards,
+                deck_translation,
+                &timing,
+                &mut StdRng::seed_from_u64(seed),
+            ),
+            None => shuffle
+                .style
+                .plan(&cards, deck_translation, &timing, rng.rng()),
+        };
+        let (total, mut resting) =
+            restack(&mut commands, plan, deck_translation, &timing, &settings);
+        resting.sort_by(|a, b| b.1.translation.y.total_cmp(&a.1.translation.y));
+        ew_shuffled.write(DeckShuffled {
+            deck_entity: shuffle.deck_entity,
+            cards: resting.iter().map(|(entity, _)| *entity).collect(),
+        });
+        rest_on_deck(&mut query_cards, resting);
+        queue.hold(&zones, now + total);
+    }
+}
 
-            let initial_translation = transform.translation;
-            let new_offset = Vec3::new(deck_translation.x, i as f32 * 0.01, deck_translation.z);
+pub fn handle_reveal_card<T>(
+    mut er_reveal: MessageReader<RevealCard<T>>,
+    mut q_cards: Query<&mut Card<T>>,
+    mut card_index: ResMut<CardIndex<T>>,
+    mut ew_error: MessageWriter<LaMesaError>,
+) where
+    T: Send + Clone + Sync + Debug + CardMetadata + 'static,
+{
+    for reveal in er_reveal.read() {
+        let Ok(mut card) = q_cards.get_mut(reveal.card_entity) else {
+            ew_error.write(LaMesaError::UnknownCard {
+                card_entity: reveal.card_entity,
+            });
+            continue;
+        };
+        card.data = reveal.data.clone();
+        card_index.set_key(reveal.card_entity, reveal.data.key());
+    }
+}
 
-            let idle_tween = Tween::new(
-                EaseFunction::QuadraticIn,
-                Duration::from_millis(duration * i as u64),
-                TransformPositionLens {
-                    start: initial_translation,
-                    end: initial_translation,
-                },
-            );
+/// Show the new face of revealed cards. The face material is updated in place, so highlights
+/// applied to it stay.
+#[cfg(feature = "render")]
+pub fn handle_reveal_face<T>(
+    mut er_reveal: MessageReader<RevealCard<T>>,
+    q_cards: Query<&Children, With<Card<T>>>,
+    q_faces: Query<&MeshMaterial3d<StandardMaterial>, With<CardFace>>,
+    mut assets: CardAssets,
+) where
+    T: Send + Clone + Sync + Debug + CardMetadata + 'static,
+{
+    let (Some(materials), Some(asset_server)) =
+        (assets.materials.as_mut(), assets.asset_server.as_ref())
+    else {
+        er_reveal.clear();
+        return;
+    };
+
+    for reveal in er_reveal.read() {
+        let Ok(children) = q_cards.get(reveal.card_entity) else {
+            continue;
+        };
+        for face in children.iter().filter_map(|child| q_faces.get(child).ok()) {
+            if let Some(material) = materials.get_mut(&face.0) {
+                material.base_color_texture =
+                    Some(asset_server.load(reveal.data.front_image_filename()));
+            }
+        }
+    }
+}
 
-            let tween1 = Tween::new(
-                EaseFunction::QuadraticIn,
-                Duration::from_millis(duration),
-                TransformPositionLens {
-                    start: initial_translation,
-                    end: initial_translation + random_offset,
-                },
-            );
+pub fn handle_reorder_deck<T>(
+    mut commands: Commands,
+    mut er_reorder: MessageReader<ReorderDeck<T>>,
+    card_index: Res<CardIndex<T>>,
+    mut query_cards: Query<(Entity, &mut Card<T>, &Transform, &Deck)>,
+    query_deck: Query<(Entity, &Transform, &DeckArea), Without<Deck>>,
+    deck_areas: Query<&DeckArea>,
+    mut ew_error: MessageWriter<LaMesaError>,
+    mut queue: ResMut<OperationQueue>,
+    mut pending: Local<Vec<Queued<ReorderDeck<T>>>>,
+    mut ew_reordered: MessageWriter<DeckReordered>,
+    time: Res<Time>,
+    settings: Res<AnimationSettings>,
+) where
+    T: Send + Clone + Sync + Debug + CardMetadata + 'static,
+{
+    let now = time.elapsed();
+    for reorder in er_reorder.read() {
+        let zones = deck_zones(&deck_areas, reorder.deck_entity);
+        pending.push(queue.enqueue(zones, reorder.clone()));
+    }
 
-            let tween2 = Tween::new(
-                EaseFunction::QuadraticIn,
-                Duration::from_millis(duration),
-                TransformPositionLens {
-                    start: initial_translation + random_offset,
-                    end: deck_translation + random_offset + Vec3::new(0.0, i as f32 * 0.01, 0.0),
-                },
-            );
+    while let Some(Queued {
+        message: event,
+        zones,
+        ..
+    }) = queue.next_ready(&mut pending, now)
+    {
+        let Ok((_, deck_transform, deck_area)) = query_deck.get(event.deck_entity) else {
+            ew_error.write(LaMesaError::UnknownDeck {
+                deck_entity: event.deck_entity,
+            });
+            continue;
+        };
 
-            let tween3 = Tween::new(
-                EaseFunction::QuadraticIn,
-                Duration::from_millis(duration),
-                TransformPositionLens {
-                    start: deck_translation + random_offset + Vec3::new(0.0, i as f32 * 0.01, 0.0),
-                    end: new_offset,
-                },
-            );
+        // list all cards whose parent is deck, bottom card first
+        let mut cards: Vec<(Entity, &Card<T>, &Transform)> = query_cards
+            .iter()
+            .filter(|(_, _, _, deck)| deck.marker == deck_area.marker)
+            .map(|(entity, card, transform, _)| (entity, card, transform))
+            .collect();
+        cards.sort_by(|a, b| a.2.translation.y.partial_cmp(&b.2.translation.y).unwrap());
+
+        let data: Vec<(Entity, &T)> = cards
+            .iter()
+            .map(|(entity, card, _)| (*entity, &card.data))
+            .collect();
+        let order = match event.op.order(&data) {
+            Ok(order) => order,
+            Err(card_entity) => {
+                ew_error.write(LaMesaError::CardNotInZone {
+                    card_entity,
+                    zone: card_index.zone_of(card_entity),
+                });
+                ew_reordered.write(DeckReordered {
+                    deck_entity: event.deck_entity,
+                    cards: cards.iter().rev().map(|(entity, _, _)| *entity).collect(),
+                });
+                continue;
+            }
+        };
+        ew_reordered.write(DeckReordered {
+            deck_entity: event.deck_entity,
+            cards: order.iter().rev().map(|i| cards[*i].0).collect(),
+        });
 
-            let seq = idle_tween.then(tween1).then(tween2).then(tween3);
+        let cards: Vec<(Entity, Transform)> = cards
+            .iter()
+            .map(|(entity, _, transform)| (*entity, **transform))
+            .collect();
+        let timing = settings.resolve(settings.reorder, event.timing);
+        let mut deck_translation = deck_transform.translation;
+        deck_translation.y = 0.0;
 
-            commands.entity(*entity).insert(TweenAnim::new(seq));
-        }
-    });
+        let plan = reorder(&cards, &order, deck_translation, &timing);
+        let (total, resting) = restack(&mut commands, plan, deck_translation, &timing, &settings);
+        rest_on_deck(&mut query_cards, resting);
+        queue.hold(&zones, now + total);
+    }
 }
 
 pub fn handle_place_card_on_table<T>(
     mut commands: Commands,
     mut place_card_on_table: MessageReader<PlaceCardOnTable>,
+    mut index: ParamSet<(ResMut<CardIndex<T>>, RulesCheck<T>)>,
     mut set: ParamSet<(
-        Query<(Entity, &mut Transform, &PlayArea)>,
-        Query<(Entity, &Card<T>, &mut Transform)>,
+        Query<(Entity, &Transform, &PlayArea)>,
+        Query<(Entity, &Card<T>, &Transform)>,
     )>,
+    mut ew_error: MessageWriter<LaMesaError>,
+    mut queue: ResMut<OperationQueue>,
+    mut pending: Local<Vec<Queued<PlaceCardOnTable>>>,
+    time: Res<Time>,
+    settings: Res<AnimationSettings>,
 ) where
     T: Send + Clone + Sync + Debug + 'static,
 {
+    let now = time.elapsed();
     for event in place_card_on_table.read() {
+        let mut zones = vec![CardZone::Table {
+            marker: event.marker,
+            player: event.player,
+        }];
+        zones.extend(index.p0().zone_of(event.card_entity));
+        pending.push(queue.enqueue(zones, event.clone()));
+    }
+
+    while let Some(Queued {
+        message: event,
+        zones,
+        ..
+    }) = queue.next_ready(&mut pending, now)
+    {
+        // the rules see the table as the earlier operations left it
+        if !index
+            .p1()
+            .allows_play(event.card_entity, event.marker, event.player)
+        {
+            ew_error.write(LaMesaError::PlayRefused {
+                card_entity: event.card_entity,
+                marker: event.marker,
+                player: event.player,
+            });
+            continue;
+        }
+        let mut card_index = index.p0();
+
         let binding = set.p0();
-        let play_area_transform = binding
+        let Some(play_area_transform) = binding
             .iter()
             .find(|(_, _, play_area)| {
                 play_area.marker == event.marker && play_area.player == event.player
             })
-            .map(|(_, transform, _)| transform);
-
-        if play_area_transform.is_none() {
+            .map(|(_, transform, _)| transform)
+        else {
+            ew_error.write(LaMesaError::UnknownPlayArea {
+                marker: event.marker,
+                player: event.player,
+            });
             continue;
-        }
-        let play_area_transform = play_area_transform.unwrap();
+        };
         let play_area_translation = play_area_transform.translation;
         let play_area_rotation = play_area_transform.rotation;
 
         let binding = set.p1();
-        let card_transform = binding
-            .get(event.card_entity)
-            .map(|(_, _, transform)| transform)
-            .unwrap();
-        let card_translation = card_transform.translation;
-        let card_rotation = card_transform.rotation;
-
-        let duration = 75;
-
-        let tween0 = Tween::new(
-            EaseFunction::QuadraticIn,
-            Duration::from_millis(duration),
-            TransformRotationLens {
-                start: card_rotation,
-                end: play_area_rotation,
-            },
-        );
+        let Ok((_, card, card_transform)) = binding.get(event.card_entity) else {
+            ew_error.write(LaMesaError::UnknownCard {
+                card_entity: event.card_entity,
+            });
+            continue;
+        };
+
+        // only cards held in hand or already on the table can be placed
+        let zone = card_index.zone_of(event.card_entity);
+        if !matches!(zone, Some(CardZone::Hand { .. } | CardZone::Table { .. })) {
+            ew_error.write(LaMesaError::CardNotInZone {
+                card_entity: event.card_entity,
+                zone,
+            });
+            continue;
+        }
 
-        let tween1 = Tween::new(
-            EaseFunction::QuadraticIn,
-            Duration::from_millis(duration),
-            TransformPositionLens {
-                start: card_translation,
-                end: play_area_translation,
-            },
+        let start = *card_transform;
+        let end = Transform {
+            translation: play_area_translation,
+            rotation: play_area_rotation,
+            scale: start.scale,
+        };
+
+        let timing = settings.resolve(settings.place_on_table, event.timing);
+        let seq = flight(start, end, Duration::ZERO, &timing, settings.flight_path);
+
+        // hover and selection move the card relative to where it rests now
+        let card = Card::<T> {
+            pickable: card.pickable,
+            transform: Some(end),
+            data: card.data.clone(),
+        };
+
+        card_index.set_zone(
+            event.card_entity,
+            Some(CardZone::Table {
+                marker: event.marker,
+                player: event.player,
+            }),
         );
 
-        let seq = tween0.then(tween1);
-
+        // an attached card placed on its own leaves its host, even on the same play area
         commands
             .entity(event.card_entity)
-            .remove::<Hand>()
-            .insert(CardOnTable {
-                marker: event.marker,
-                player: event.player,
-            })
-            .insert(TweenAnim::new(seq));
+            .remove::<(Hand, Attached)>()
+            .insert((
+                CardOnTable {
+                    marker: event.marker,
+                    player: event.player,
+                },
+                card,
+            ));
+        animate(
+            &mut commands,
+            event.card_entity,
+            TweenAnim::new(seq),
+            end,
+            &settings,
+        );
+
+        queue.hold(&zones, now + timing.duration);
     }
 }
 
 pub fn handle_discard_card_to_deck<T>(
     mut commands: Commands,
     mut place_card_off_table: MessageReader<DiscardCardToDeck>,
+    mut index: ParamSet<(ResMut<CardIndex<T>>, RulesCheck<T>)>,
     mut set: ParamSet<(
-        Query<(Entity, &mut Transform, &Card<T>)>,
-        Query<(Entity, &mut Transform, &DeckArea)>,
-        Query<(Entity, &mut Transform, &Deck)>,
+        Query<(Entity, &Transform, &Card<T>)>,
+        Query<(Entity, &Transform, &DeckArea)>,
+        Query<(Entity, &Transform, &Deck)>,
     )>,
+    deck_areas: Query<&DeckArea>,
+    mut ew_error: MessageWriter<LaMesaError>,
+    mut queue: ResMut<OperationQueue>,
+    mut pending: Local<Vec<Queued<DiscardCardToDeck>>>,
+    time: Res<Time>,
+    settings: Res<AnimationSettings>,
 ) where
     T: Send + Clone + Sync + Debug + 'static,
 {
-    let duration = 150;
+    let now = time.elapsed();
     for event in place_card_off_table.read() {
-        let binding = set.p0();
+        let mut zones = deck_zones(&deck_areas, event.deck_entity);
+        zones.extend(index.p0().zone_of(event.card_entity));
+        pending.push(queue.enqueue(zones, event.clone()));
+    }
 
-        if binding.get(event.card_entity).is_err() {
+    while let Some(Queued {
+        message: event,
+        zones,
+        ..
+    }) = queue.next_ready(&mut pending, now)
+    {
+        // the rules see the table as the earlier operations left it
+        if !index
+            .p1()
+            .allows_discard(event.card_entity, event.deck_entity)
+        {
+            ew_error.write(LaMesaError::DiscardRefused {
+                card_entity: event.card_entity,
+                deck_entity: event.deck_entity,
+            });
             continue;
         }
+        let mut card_index = index.p0();
 
-        let card_transform = binding
-            .get(event.card_entity)
-            .map(|(_, transform, _)| transform)
-            .unwrap();
+        let binding = set.p0();
+        let Ok((_, card_transform, card)) = binding.get(event.card_entity) else {
+            ew_error.write(LaMesaError::UnknownCard {
+                card_entity: event.card_entity,
+            });
+            continue;
+        };
+
+        // cards that are being despawned have no zone
+        let zone = card_index.zone_of(event.card_entity);
+        if zone.is_none() {
+            ew_error.write(LaMesaError::CardNotInZone {
+                card_entity: event.card_entity,
+                zone,
+            });
+            continue;
+        }
 
-        let card_translation = card_transform.translation;
-        let card_rotation = card_transform.rotation;
+        let start = *card_transform;
+        let (pickable, data) = (card.pickable, card.data.clone());
 
         // get highest card on deck
         let binding = set.p1();
-        let discard_deck_marker = binding.get(event.deck_entity).unwrap().2.marker;
+        let Ok((_, deck_transform, deck_area)) = binding.get(event.deck_entity) else {
+            ew_error.write(LaMesaError::UnknownDeck {
+                deck_entity: event.deck_entity,
+            });
+            continue;
+        };
+        let discard_deck_marker = deck_area.marker;
 
         let deck_transform = binding
             .iter()
             .filter(|(_, _, deck)| deck.marker == discard_deck_marker)
             .max_by_key(|(_, transform, _)| (transform.translation.y * 100.0) as usize)
-            .unwrap()
-            .1;
+            .map_or(deck_transform, |(_, transform, _)| transform);
         let deck_translation = deck_transform.translation;
         let deck_rotation = deck_transform.rotation;
 
@@ -362,61 +1008,125 @@ pub fn handle_discard_card_to_deck<T>(
         let final_translation =
             deck_translation + Vec3::new(0.0, number_cards_on_deck as f32 * 0.01, 0.0);
 
-        let tween0 = Tween::new(
-            EaseFunction::QuadraticIn,
-            Duration::from_millis(duration),
-            TransformRotationLens {
-                start: card_rotation,
-                end: deck_rotation * Quat::from_rotation_x(std::f32::consts::PI),
-            },
-        );
-
-        let tween1 = Tween::new(
-            EaseFunction::QuadraticIn,
-            Duration::from_millis(duration),
-            TransformPositionLens {
-                start: card_translation,
-                end: final_translation,
-            },
+        let timing = settings.resolve(settings.discard, event.timing);
+        let final_rotation = deck_rotation * Quat::from_rotation_x(std::f32::consts::PI);
+
+        let end = Transform {
+            translation: final_translation,
+            rotation: final_rotation,
+            scale: start.scale,
+        };
+        let seq = flight(start, end, Duration::ZERO, &timing, settings.flight_path);
+
+        // the deck is ordered by where its cards come to rest, not where they are mid-flight
+        let card = Card::<T> {
+            pickable,
+            transform: Some(end),
+            data,
+        };
+
+        card_index.set_zone(
+            event.card_entity,
+            Some(CardZone::Deck {
+                marker: discard_deck_marker,
+            }),
         );
 
-        let seq = tween0.then(tween1);
-
         commands
             .entity(event.card_entity)
             .remove::<Hand>()
             .remove::<CardOnTable>()
-            .insert(Deck {
-                marker: discard_deck_marker,
-            })
-            .insert(TweenAnim::new(seq));
-    }
+            .insert((
+                Deck {
+                    marker: discard_deck_marker,
+                },
+                card,
+            ));
+        animate(
+            &mut commands,
+            event.card_entity,
+            TweenAnim::new(seq),
+            end,
+            &settings,
+        );
+
+        queue.hold(&zones, now + timing.duration);
+    }
 }
 
 pub fn handle_draw_to_table<T>(
     mut commands: Commands,
     mut er_draw_hand: MessageReader<DrawToTable>,
-    q_play_area_area: Query<(Entity, &mut Transform, &PlayArea)>,
-    q_cards: Query<(Entity, &Card<T>, &mut Transform, &Deck), Without<PlayArea>>,
+    mut index: ParamSet<(ResMut<CardIndex<T>>, RulesCheck<T>)>,
+    q_play_area_area: Query<(Entity, &Transform, &PlayArea)>,
+    q_cards: Query<(Entity, &Card<T>, &Transform, &Deck), Without<PlayArea>>,
     q_decks: Query<(Entity, &DeckArea)>,
+    deck_areas: Query<&DeckArea>,
+    mut ew_error: MessageWriter<LaMesaError>,
+    mut queue: ResMut<OperationQueue>,
+    mut pending: Local<Vec<Queued<DrawToTable>>>,
+    time: Res<Time>,
+    settings: Res<AnimationSettings>,
 ) where
     T: Send + Clone + Sync + Debug + CardMetadata + 'static,
 {
-    let offset = Vec3::new(0.0, -0.0, 2.6);
-
-    er_draw_hand.read().for_each(|draw| {
-        let offset = offset
-            * match draw.player {
-                1 => 1.0,
-                _ => -1.0,
-            };
+    let now = time.elapsed();
+    for draw in er_draw_hand.read() {
+        let mut zones = deck_zones(&deck_areas, draw.deck_entity);
+        zones.extend(draw.play_area_markers.iter().map(|marker| CardZone::Table {
+            marker: *marker,
+            player: draw.player,
+        }));
+        pending.push(queue.enqueue(zones, draw.clone()));
+    }
 
-        let result = q_decks.get(draw.deck_entity);
-        if result.is_err() {
-            return;
+    while let Some(Queued {
+        message: draw,
+        zones,
+        ..
+    }) = queue.next_ready(&mut pending, now)
+    {
+        // the rules see the table as the earlier operations left it
+        if !index.p1().allows_draw(draw.deck_entity, draw.player) {
+            ew_error.write(LaMesaError::DrawRefused {
+                deck_entity: draw.deck_entity,
+                player: draw.player,
+            });
+            continue;
         }
+        let mut card_index = index.p0();
 
-        let draw_deck = result.unwrap().1;
+        let timing = settings.resolve(settings.draw_to_table, draw.timing);
+
+        let Ok((_, draw_deck)) = q_decks.get(draw.deck_entity) else {
+            ew_error.write(LaMesaError::UnknownDeck {
+                deck_entity: draw.deck_entity,
+            });
+            continue;
+        };
+
+        // every play area has to exist before any card starts moving
+        let play_area_transforms = draw
+            .play_area_markers
+            .iter()
+            .map(|marker| {
+                q_play_area_area
+                    .iter()
+                    .find(|(_, _, area)| area.marker == *marker && area.player == draw.player)
+                    .map(|(_, transform, _)| transform)
+                    .ok_or(LaMesaError::UnknownPlayArea {
+                        marker: *marker,
+                        player: draw.player,
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>();
+        let play_area_transforms = match play_area_transforms {
+            Ok(play_area_transforms) => play_area_transforms,
+            Err(error) => {
+                ew_error.write(error);
+                continue;
+            }
+        };
 
         let cards: Vec<(Entity, &Card<T>, &Transform)> = q_cards
             .iter()
@@ -434,192 +1144,121 @@ pub fn handle_draw_to_table<T>(
             .take(draw.play_area_markers.len())
             .enumerate()
         {
-            let initial_translation = transform.translation;
-            let initial_rotation = transform.rotation;
-            let new_offset = Vec3::new(0.0, i as f32 * 0.01, 0.0);
             let play_area_marker = draw.play_area_markers[i];
-
-            // find deck by deck_marker
-            let play_area_transform = q_play_area_area
-                .iter()
-                .find(|(_, _, deck)| deck.marker == play_area_marker && deck.player == draw.player)
-                .unwrap()
-                .1;
-            let play_area_translation = play_area_transform.translation;
-            let play_area_rotation = play_area_transform.rotation;
-
-            let idle_tween = Tween::new(
-                EaseFunction::QuadraticIn,
-                Duration::from_millis((draw.duration * 4) * (i) as u64),
-                TransformPositionLens {
-                    start: initial_translation,
-                    end: initial_translation,
-                },
-            );
-
-            let slide = initial_translation + offset;
-            let tween1 = Tween::new(
-                EaseFunction::QuadraticIn,
-                Duration::from_millis(draw.duration),
-                TransformPositionLens {
-                    start: initial_translation,
-                    end: slide,
-                },
-            );
-
-            let mut slide_flat = slide;
-            slide_flat.y = 0.0;
-
-            let tween2 = Tween::new(
-                EaseFunction::QuadraticIn,
-                Duration::from_millis(draw.duration),
-                TransformPositionLens {
-                    start: slide,
-                    end: slide_flat + new_offset,
-                },
-            );
-
-            let tween3 = Tween::new(
-                EaseFunction::QuadraticIn,
-                Duration::from_millis(
-                    (draw.duration * 4) * (draw.play_area_markers.len() - i) as u64,
-                ),
-                TransformPositionLens {
-                    start: slide_flat + new_offset,
-                    end: slide_flat + new_offset,
-                },
-            );
-
-            // rotate angle depends on who player is
-            let end_rotation = match draw.player {
-                1 => Quat::from_rotation_x(std::f32::consts::FRAC_PI_2),
-                _ => {
-                    Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2)
-                        * Quat::from_rotation_y(std::f32::consts::PI)
-                }
+            let play_area_transform = play_area_transforms[i];
+            let end = Transform {
+                translation: play_area_transform.translation,
+                rotation: play_area_transform.rotation,
+                scale: transform.scale,
             };
 
-            let tween4 = Tween::new(
-                EaseFunction::QuadraticIn,
-                Duration::from_millis(draw.duration),
-                TransformRotationLens {
-                    start: initial_rotation,
-                    end: end_rotation,
-                },
+            let seq = flight(
+                **transform,
+                end,
+                timing.stagger * i as u32,
+                &timing,
+                settings.flight_path,
             );
 
-            let tween5 = Tween::new(
-                EaseFunction::QuadraticIn,
-                Duration::from_millis(draw.duration),
-                TransformPositionLens {
-                    start: slide_flat + new_offset,
-                    end: slide_flat + new_offset,
-                },
-            );
-
-            let tween6 = Tween::new(
-                EaseFunction::QuadraticIn,
-                Duration::from_millis(draw.duration),
-                TransformRotationLens {
-                    start: end_rotation,
-                    end: play_area_rotation,
-                },
-            );
-
-            let tween7 = Tween::new(
-                EaseFunction::QuadraticIn,
-                Duration::from_millis(draw.duration),
-                TransformPositionLens {
-                    start: slide_flat + new_offset,
-                    end: play_area_translation,
-                },
-            );
-
-            let seq = idle_tween
-                .then(tween1)
-                .then(tween2)
-                .then(tween3)
-                .then(tween4)
-                .then(tween5)
-                .then(tween6)
-                .then(tween7);
-
             let card = Card::<T> {
                 pickable: true,
-                transform: Some(Transform::from_translation(play_area_translation)),
+                transform: Some(Transform::from_translation(end.translation)),
                 data: card.data.clone(),
             };
 
+            card_index.set_zone(
+                *entity,
+                Some(CardZone::Table {
+                    marker: play_area_marker,
+                    player: draw.player,
+                }),
+            );
+
             commands
                 .entity(*entity)
-                .insert(TweenAnim::new(seq))
                 .insert(CardOnTable {
                     marker: play_area_marker,
                     player: draw.player,
                 })
                 .remove::<Deck>()
                 .insert(card);
+            animate(&mut commands, *entity, TweenAnim::new(seq), end, &settings);
         }
-    });
+
+        let total = timing.stagger * draw.play_area_markers.len().saturating_sub(1) as u32
+            + timing.duration;
+        queue.hold(&zones, now + total);
+    }
 }
 
 pub fn handle_draw_to_hand<T>(
     mut commands: Commands,
     mut er_draw_hand: MessageReader<DrawToHand>,
+    mut index: ParamSet<(ResMut<CardIndex<T>>, RulesCheck<T>)>,
     mut set: ParamSet<(
-        Query<(Entity, &mut Transform, &HandArea)>,
-        Query<(Entity, &mut Transform, &DeckArea)>,
-        Query<(Entity, &Card<T>, &mut Transform, &Deck)>,
+        Query<(Entity, &Transform, &HandArea)>,
+        Query<(Entity, &Transform, &DeckArea)>,
+        Query<(Entity, &Card<T>, &Transform, &Deck)>,
     )>,
-    cards_in_hand: Query<&Hand>,
+    deck_areas: Query<&DeckArea>,
+    mut ew_error: MessageWriter<LaMesaError>,
+    mut queue: ResMut<OperationQueue>,
+    mut pending: Local<Vec<Queued<DrawToHand>>>,
+    time: Res<Time>,
+    settings: Res<AnimationSettings>,
 ) where
     T: Send + Clone + Sync + Debug + CardMetadata + 'static,
 {
-    let duration = 75;
-    let offset = Vec3::new(0.0, -0.0, 2.6);
-
-    er_draw_hand.read().for_each(|draw| {
-        let offset = offset
-            * match draw.player {
-                1 => 1.0,
-                _ => -1.0,
-            };
+    let now = time.elapsed();
+    for draw in er_draw_hand.read() {
+        let mut zones = deck_zones(&deck_areas, draw.deck_entity);
+        zones.push(CardZone::Hand {
+            player: draw.player,
+        });
+        pending.push(queue.enqueue(zones, draw.clone()));
+    }
+
+    while let Some(Queued {
+        message: draw,
+        zones,
+        ..
+    }) = queue.next_ready(&mut pending, now)
+    {
+        // the rules see the table as the earlier operations left it
+        if !index.p1().allows_draw(draw.deck_entity, draw.player) {
+            ew_error.write(LaMesaError::DrawRefused {
+                deck_entity: draw.deck_entity,
+                player: draw.player,
+            });
+            continue;
+        }
+        let mut card_index = index.p0();
+
+        let timing = settings.resolve(settings.draw_to_hand, draw.timing);
 
         // find global position of hand with player number
         let binding = set.p0();
-        let q = binding
+        let Some(hand_transform) = binding
             .iter()
             .find(|(_, _, hand)| hand.player == draw.player)
-            .map(|(_, transform, _)| transform);
-
-        if q.is_none() {
-            return;
-        }
-
-        let hand_transform = q.unwrap();
-        let hand_translation = hand_transform.translation;
-        let hand_rotation = hand_transform.rotation;
+            .map(|(_, transform, _)| transform)
+        else {
+            ew_error.write(LaMesaError::UnknownHand {
+                player: draw.player,
+            });
+            continue;
+        };
+        let hand_transform = *hand_transform;
 
         // find position of deck
         let binding = set.p1();
-
-        let deck = binding.get(draw.deck_entity);
-        if deck.is_err() {
-            return;
-        }
-
-        let deck = deck.unwrap();
-        let hand_deck_marker = deck.2.marker;
-        // find deck by deck_marker
-        let deck_transform = binding
-            .iter()
-            .find(|(_, _, deck)| deck.marker == hand_deck_marker)
-            .unwrap()
-            .1;
-        let deck_translation = deck_transform.translation;
-        // deck_translation.z = 0.0;
-        let _deck_rotation = deck_transform.rotation;
-        let hand_deck_offset = deck_translation - hand_translation;
+        let Ok((_, _, deck_area)) = binding.get(draw.deck_entity) else {
+            ew_error.write(LaMesaError::UnknownDeck {
+                deck_entity: draw.deck_entity,
+            });
+            continue;
+        };
+        let hand_deck_marker = deck_area.marker;
 
         // list all cards whose parent is deck
         let binding = set.p2();
@@ -633,152 +1272,130 @@ pub fn handle_draw_to_hand<T>(
         let mut sorted = cards.clone();
         sorted.sort_by(|a, b| b.2.translation.y.partial_cmp(&a.2.translation.y).unwrap());
 
-        // number cards in hand
-        let cards_in_hand = cards_in_hand
-            .iter()
-            .filter(|hand| hand.player == draw.player)
-            .count();
-        let cards_to_draw = draw.num_cards - cards_in_hand;
+        // number cards in hand, including the ones still flying there
+        let cards_in_hand = cards_in_hand(&card_index, draw.player);
+        let cards_to_draw = draw.num_cards.saturating_sub(cards_in_hand);
         // draw the first `num_cards` cards
         for (i, (entity, card, transform)) in sorted.iter_mut().take(cards_to_draw).enumerate() {
-            let initial_translation = transform.translation;
-
-            let initial_rotation = transform.rotation;
-            let new_offset = Vec3::new(0.0, i as f32 * 0.01, 0.0);
-
-            let idle_tween = Tween::new(
-                EaseFunction::QuadraticIn,
-                Duration::from_millis((duration * 4) * (i) as u64),
-                TransformPositionLens {
-                    start: initial_translation,
-                    end: initial_translation,
-                },
-            );
-
-            let slide = initial_translation + offset;
-            let tween1 = Tween::new(
-                EaseFunction::QuadraticIn,
-                Duration::from_millis(duration),
-                TransformPositionLens {
-                    start: initial_translation,
-                    end: slide,
-                },
-            );
-
-            let mut slide_flat = slide;
-            slide_flat.y = 0.0;
-
-            let tween2 = Tween::new(
-                EaseFunction::QuadraticIn,
-                Duration::from_millis(duration),
-                TransformPositionLens {
-                    start: slide,
-                    end: slide_flat + new_offset,
-                },
-            );
-
-            let tween3 = Tween::new(
-                EaseFunction::QuadraticIn,
-                Duration::from_millis((duration * 4) * (draw.num_cards - i) as u64),
-                TransformPositionLens {
-                    start: slide_flat + new_offset,
-                    end: slide_flat + new_offset,
-                },
+            fly_to_hand(
+                &mut commands,
+                &mut card_index,
+                *entity,
+                *card,
+                **transform,
+                hand_slot(&hand_transform, cards_in_hand + i),
+                draw.player,
+                timing.stagger * i as u32,
+                &timing,
+                &settings,
             );
+        }
 
-            // rotate angle depends on who player is
-            let end_rotation = match draw.player {
-                1 => Quat::from_rotation_x(std::f32::consts::FRAC_PI_2),
-                _ => {
-                    Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2)
-                        * Quat::from_rotation_y(std::f32::consts::PI)
-                }
-            };
+        let total = timing.stagger * cards_to_draw.saturating_sub(1) as u32 + timing.duration;
+        queue.hold(&zones, now + total);
+    }
+}
 
-            let tween4 = Tween::new(
-                EaseFunction::QuadraticIn,
-                Duration::from_millis(duration),
-                TransformRotationLens {
-                    start: initial_rotation,
-                    end: end_rotation,
-                },
-            );
+pub fn handle_deal<T>(
+    mut commands: Commands,
+    mut er_deal: MessageReader<Deal>,
+    mut card_index: ResMut<CardIndex<T>>,
+    q_hand_areas: Query<(&Transform, &HandArea)>,
+    q_deck_areas: Query<&DeckArea>,
+    q_cards: Query<(Entity, &Card<T>, &Transform, &Deck)>,
+    mut ew_error: MessageWriter<LaMesaError>,
+    mut queue: ResMut<OperationQueue>,
+    mut pending: Local<Vec<Queued<Deal>>>,
+    time: Res<Time>,
+    settings: Res<AnimationSettings>,
+) where
+    T: Send + Clone + Sync + Debug + 'static,
+{
+    let now = time.elapsed();
+    for deal in er_deal.read() {
+        let mut zones = deck_zones(&q_deck_areas, deal.deck_entity);
+        zones.extend(
+            deal.players
+                .iter()
+                .map(|player| CardZone::Hand { player: *player }),
+        );
+        pending.push(queue.enqueue(zones, deal.clone()));
+    }
 
-            let tween5 = Tween::new(
-                EaseFunction::QuadraticIn,
-                Duration::from_millis(duration),
-                TransformPositionLens {
-                    start: slide_flat + new_offset,
-                    end: slide_flat + new_offset - hand_deck_offset,
-                },
-            );
+    while let Some(Queued {
+        message: deal,
+        zones,
+        ..
+    }) = queue.next_ready(&mut pending, now)
+    {
+        let timing = settings.resolve(settings.deal, deal.timing);
+
+        let Ok(deck_area) = q_deck_areas.get(deal.deck_entity) else {
+            ew_error.write(LaMesaError::UnknownDeck {
+                deck_entity: deal.deck_entity,
+            });
+            continue;
+        };
 
-            let tween6 = Tween::new(
-                EaseFunction::QuadraticIn,
-                Duration::from_millis(duration),
-                TransformRotationLens {
-                    start: end_rotation,
-                    end: hand_rotation,
-                },
-            );
+        // every seat has to exist before any card starts moving
+        let hands = deal
+            .players
+            .iter()
+            .map(|player| {
+                q_hand_areas
+                    .iter()
+                    .find(|(_, hand)| hand.player == *player)
+                    .map(|(transform, _)| {
+                        (*player, *transform, cards_in_hand(&card_index, *player))
+                    })
+                    .ok_or(LaMesaError::UnknownHand { player: *player })
+            })
+            .collect::<Result<Vec<_>, _>>();
+        let mut hands = match hands {
+            Ok(hands) => hands,
+            Err(error) => {
+                ew_error.write(error);
+                continue;
+            }
+        };
 
-            let end_transform = hand_translation
-                + Vec3::new(
-                    (cards_in_hand + i) as f32 * 2.6 - DECK_WIDTH / 2.0,
-                    0.0,
-                    0.0,
-                );
-            let tween7 = Tween::new(
-                EaseFunction::QuadraticIn,
-                Duration::from_millis(duration),
-                TransformPositionLens {
-                    start: slide_flat + new_offset - hand_deck_offset,
-                    end: end_transform,
-                },
+        let mut cards: Vec<(Entity, &Card<T>, &Transform)> = q_cards
+            .iter()
+            .filter(|(_, _, _, deck)| deck.marker == deck_area.marker)
+            .map(|(entity, card, transform, _)| (entity, card, transform))
+            .collect();
+        cards.sort_by(|a, b| b.2.translation.y.partial_cmp(&a.2.translation.y).unwrap());
+
+        // seat receiving each card, in dealing order
+        let seats: Vec<usize> = match deal.order {
+            DealOrder::RoundRobin => (0..deal.cards_each).flat_map(|_| 0..hands.len()).collect(),
+            DealOrder::Batch => (0..hands.len())
+                .flat_map(|seat| std::iter::repeat_n(seat, deal.cards_each))
+                .collect(),
+        };
+
+        let mut dealt = 0usize;
+        for (seat, (entity, card, transform)) in seats.into_iter().zip(cards) {
+            let (player, hand_transform, slot) = &mut hands[seat];
+            fly_to_hand(
+                &mut commands,
+                &mut card_index,
+                entity,
+                card,
+                *transform,
+                hand_slot(hand_transform, *slot),
+                *player,
+                timing.stagger * dealt as u32,
+                &timing,
+                &settings,
             );
-
-            let seq = idle_tween
-                .then(tween1)
-                .then(tween2)
-                .then(tween3)
-                .then(tween4)
-                .then(tween5)
-                .then(tween6)
-                .then(tween7);
-
-            let card = Card::<T> {
-                pickable: true,
-                transform: Some(Transform::from_translation(end_transform)),
-                data: card.data.clone(),
-            };
-
-            commands
-                .entity(*entity)
-                .insert(TweenAnim::new(seq))
-                .remove::<Deck>()
-                // .insert(PickableBundle::default())
-                .insert(card);
-
-            let pause = Duration::from_millis((duration * 4) * (i) as u64)
-                + Duration::from_millis(duration)
-                + Duration::from_millis(duration)
-                + Duration::from_millis((duration * 4) * (draw.num_cards - i) as u64)
-                + Duration::from_millis(duration)
-                + Duration::from_millis(duration)
-                + Duration::from_millis(duration)
-                + Duration::from_millis(duration);
-            let pause = pause.as_secs_f32();
-
-            let card_entity = entity.clone();
-            let player = draw.player;
-            commands.spawn_task(move || async move {
-                AsyncWorld.sleep(pause).await;
-                AsyncWorld.entity(card_entity).insert(Hand { player })?;
-
-                Ok(())
-            });
+            *slot += 1;
+            dealt += 1;
         }
-    });
+
+        let total = timing.stagger * dealt.saturating_sub(1) as u32 + timing.duration;
+        queue.hold(&zones, now + total);
+    }
 }
 
 pub fn preload_card_images() {}
@@ -786,88 +1403,406 @@ pub fn preload_card_images() {}
 pub fn handle_render_deck<T>(
     mut commands: Commands,
     deck: Query<(&Transform, &DeckArea)>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    asset_server: Res<AssetServer>,
+    deck_areas: Query<&DeckArea>,
+    mut assets: CardAssets,
     mut er_render_deck: MessageReader<RenderDeck<T>>,
     mut ew_deck_rendered: MessageWriter<DeckRendered>,
+    mut card_index: ResMut<CardIndex<T>>,
+    mut ew_error: MessageWriter<LaMesaError>,
+    mut queue: ResMut<OperationQueue>,
+    mut pending: Local<Vec<Queued<RenderDeck<T>>>>,
+    time: Res<Time>,
 ) where
     T: Send + Clone + Sync + Debug + CardMetadata + 'static,
 {
+    let now = time.elapsed();
     for render in er_render_deck.read() {
-        // load deck
-        let card_deck = render.deck.clone();
-        let (deck_transform, deck_area) = deck.get(render.deck_entity).unwrap();
-        let deck_translation = deck_transform.translation;
-        let deck_rotation = deck_transform.rotation;
+        let zones = deck_zones(&deck_areas, render.deck_entity);
+        pending.push(queue.enqueue(zones, render.clone()));
+    }
 
-        for (i, card) in card_deck.iter().enumerate() {
-            let back_texture = asset_server.load(card.clone().back_image_filename());
-            let back_material = materials.add(StandardMaterial {
-                base_color_texture: Some(back_texture.clone()),
-                ..Default::default()
+    while let Some(Queued {
+        message: render, ..
+    }) = queue.next_ready(&mut pending, now)
+    {
+        let Ok((deck_transform, deck_area)) = deck.get(render.deck_entity) else {
+            ew_error.write(LaMesaError::UnknownDeck {
+                deck_entity: render.deck_entity,
             });
+            continue;
+        };
+
+        spawn_deck_cards(
+            &mut commands,
+            &mut assets,
+            deck_transform,
+            deck_area,
+            &render.deck,
+            &mut card_index,
+        );
 
-            let face_texture = asset_server.load(card.clone().front_image_filename());
-            let face_material: Handle<StandardMaterial> = materials.add(StandardMaterial {
-                base_color_texture: Some(face_texture.clone()),
-                ..Default::default()
+        ew_deck_rendered.write(DeckRendered {});
+    }
+}
+
+pub fn handle_replace_deck<T>(
+    mut commands: Commands,
+    deck: Query<(&Transform, &DeckArea)>,
+    deck_areas: Query<&DeckArea>,
+    q_cards: Query<(Entity, &Deck, Option<&Children>), With<Card<T>>>,
+    q_card_parts: CardParts,
+    mut assets: CardAssets,
+    mut er_replace_deck: MessageReader<ReplaceDeck<T>>,
+    mut ew_deck_rendered: MessageWriter<DeckRendered>,
+    mut card_index: ResMut<CardIndex<T>>,
+    mut ew_error: MessageWriter<LaMesaError>,
+    mut queue: ResMut<OperationQueue>,
+    mut pending: Local<Vec<Queued<ReplaceDeck<T>>>>,
+    q_peeking: Query<(), With<Peeking>>,
+    time: Res<Time>,
+) where
+    T: Send + Clone + Sync + Debug + CardMetadata + 'static,
+{
+    let now = time.elapsed();
+    for replace in er_replace_deck.read() {
+        let zones = deck_zones(&deck_areas, replace.deck_entity);
+        pending.push(queue.enqueue(zones, replace.clone()));
+    }
+
+    // an unresolved peek would keep the deck busy forever, its cards are despawned anyway
+    for queued in pending.iter() {
+        if q_peeking.contains(queued.message.deck_entity) {
+            cancel_peek(&mut commands, &mut queue, queued.message.deck_entity);
+        }
+    }
+
+    while let Some(Queued {
+        message: replace, ..
+    }) = queue.next_ready(&mut pending, now)
+    {
+        let Ok((deck_transform, deck_area)) = deck.get(replace.deck_entity) else {
+            ew_error.write(LaMesaError::UnknownDeck {
+                deck_entity: replace.deck_entity,
             });
+            continue;
+        };
 
-            let transform = Transform::from_translation(
-                deck_translation + Vec3::new(0.0, 0.01 * (i as f32), 0.0),
-            )
-            .with_rotation(
-                deck_rotation
-                    * Quat::from_rotation_x(std::f32::consts::PI)
-                    * Quat::from_rotation_y(std::f32::consts::PI),
+        for (entity, _, children) in q_cards
+            .iter()
+            .filter(|(_, deck, _)| deck.marker == deck_area.marker)
+        {
+            despawn_card(
+                &mut commands,
+                entity,
+                children,
+                &q_card_parts,
+                &mut assets,
+                &mut card_index,
             );
+        }
 
-            // Draw Deck
-            commands
-                .spawn((
-                    Name::new("Card"),
-                    Card {
-                        pickable: false,
-                        transform: None,
-                        data: card.clone(),
-                    },
-                    Deck {
-                        marker: deck_area.marker,
-                    },
-                    Pickable::default(),
-                    Mesh3d(meshes.add(Plane3d::default().mesh().size(2.5, 3.5).subdivisions(10))),
+        spawn_deck_cards(
+            &mut commands,
+            &mut assets,
+            deck_transform,
+            deck_area,
+            &replace.deck,
+            &mut card_index,
+        );
+
+        ew_deck_rendered.write(DeckRendered {});
+    }
+}
+
+pub fn handle_clear_deck<T>(
+    mut commands: Commands,
+    mut er_clear_deck: MessageReader<ClearDeck>,
+    mut ew_error: MessageWriter<LaMesaError>,
+    q_decks: Query<&DeckArea>,
+    q_cards: Query<(Entity, &Transform, &Deck, Option<&Children>), With<Card<T>>>,
+    q_card_parts: CardParts,
+    mut assets: CardAssets,
+    mut card_index: ResMut<CardIndex<T>>,
+    mut queue: ResMut<OperationQueue>,
+    mut pending: Local<Vec<Queued<ClearDeck>>>,
+    q_peeking: Query<(), With<Peeking>>,
+    time: Res<Time>,
+    settings: Res<AnimationSettings>,
+) where
+    T: Send + Clone + Sync + Debug + 'static,
+{
+    let now = time.elapsed();
+    for clear in er_clear_deck.read() {
+        let zones = deck_zones(&q_decks, clear.deck_entity);
+        pending.push(queue.enqueue(zones, clear.clone()));
+    }
+
+    // an unresolved peek would keep the deck busy forever, its cards are despawned anyway
+    for queued in pending.iter() {
+        if q_peeking.contains(queued.message.deck_entity) {
+            cancel_peek(&mut commands, &mut queue, queued.message.deck_entity);
+        }
+    }
+
+    while let Some(Queued { message: clear, .. }) = queue.next_ready(&mut pending, now) {
+        let Ok(deck_area) = q_decks.get(clear.deck_entity) else {
+            ew_error.write(LaMesaError::UnknownDeck {
+                deck_entity: clear.deck_entity,
+            });
+            continue;
+        };
+
+        // top of the deck leaves first
+        let mut cards = q_cards
+            .iter()
+            .filter(|(_, _, deck, _)| deck.marker == deck_area.marker)
+            .collect::<Vec<_>>();
+        cards.sort_by(|a, b| b.1.translation.y.partial_cmp(&a.1.translation.y).unwrap());
+
+        for (i, (entity, transform, _, children)) in cards.into_iter().enumerate() {
+            if clear.animate {
+                animate_card_away(
+                    &mut commands,
+                    entity,
                     transform,
-                ))
-                .observe(on_card_over)
-                .observe(on_card_out)
-                .observe(on_card_click)
-                .with_children(|parent| {
-                    // face
-                    parent.spawn((
-                        Mesh3d(
-                            meshes.add(Plane3d::default().mesh().size(2.5, 3.5).subdivisions(10)),
-                        ),
-                        MeshMaterial3d(face_material),
-                    ));
+                    i,
+                    &settings,
+                    &mut card_index,
+                );
+            } else {
+                despawn_card(
+                    &mut commands,
+                    entity,
+                    children,
+                    &q_card_parts,
+                    &mut assets,
+                    &mut card_index,
+                );
+            }
+        }
+    }
+}
 
-                    // back
-                    parent.spawn((
-                        Mesh3d(
-                            meshes.add(Plane3d::default().mesh().size(2.5, 3.5).subdivisions(10)),
-                        ),
-                        MeshMaterial3d(back_material),
-                        Transform::IDENTITY
-                            .with_rotation(Quat::from_rotation_z(std::f32::consts::PI)),
-                    ));
-                });
+pub fn handle_despawn_all_cards<T>(
+    mut commands: Commands,
+    mut er_despawn_all: MessageReader<DespawnAllCards>,
+    q_cards: Query<(Entity, &Transform, Option<&Children>), (With<Card<T>>, Without<Despawning>)>,
+    q_card_parts: CardParts,
+    mut assets: CardAssets,
+    mut card_index: ResMut<CardIndex<T>>,
+    mut queue: ResMut<OperationQueue>,
+    mut pending: Local<Vec<Queued<DespawnAllCards>>>,
+    q_peeking: Query<Entity, With<Peeking>>,
+    time: Res<Time>,
+    settings: Res<AnimationSettings>,
+) where
+    T: Send + Clone + Sync + Debug + 'static,
+{
+    let now = time.elapsed();
+    for despawn in er_despawn_all.read() {
+        // every zone that holds a card or has an operation on it
+        let zones: HashSet<CardZone> = card_index
+            .iter()
+            .filter_map(|(_, entry)| entry.zone)
+            .chain(queue.zones())
+            .collect();
+        pending.push(queue.enqueue(zones.into_iter().collect(), despawn.clone()));
+    }
+
+    // unresolved peeks would keep their decks busy forever
+    if !pending.is_empty() {
+        for deck_entity in q_peeking.iter() {
+            cancel_peek(&mut commands, &mut queue, deck_entity);
         }
+    }
 
-        ew_deck_rendered.write(DeckRendered {});
+    while let Some(Queued {
+        message: despawn, ..
+    }) = queue.next_ready(&mut pending, now)
+    {
+        for (i, (entity, transform, children)) in q_cards.iter().enumerate() {
+            if despawn.animate {
+                animate_card_away(
+                    &mut commands,
+                    entity,
+                    transform,
+                    i,
+                    &settings,
+                    &mut card_index,
+                );
+            } else {
+                despawn_card(
+                    &mut commands,
+                    entity,
+                    children,
+                    &q_card_parts,
+                    &mut assets,
+                    &mut card_index,
+                );
+            }
+        }
+    }
+}
+
+pub fn handle_card_despawn<T>(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut q_despawning: Query<(Entity, &mut Despawning, Option<&Children>), With<Card<T>>>,
+    q_card_parts: CardParts,
+    mut assets: CardAssets,
+    mut card_index: ResMut<CardIndex<T>>,
+) where
+    T: Send + Clone + Sync + Debug + 'static,
+{
+    for (entity, mut despawning, children) in q_despawning.iter_mut() {
+        if despawning.timer.tick(time.delta()).is_finished() {
+            despawn_card(
+                &mut commands,
+                entity,
+                children,
+                &q_card_parts,
+                &mut assets,
+                &mut card_index,
+            );
+        }
+    }
+}
+
+/// Lift a card off the table and schedule it for despawn once the animation is over.
+fn animate_card_away<T>(
+    commands: &mut Commands,
+    entity: Entity,
+    transform: &Transform,
+    i: usize,
+    settings: &AnimationSettings,
+    card_index: &mut CardIndex<T>,
+) {
+    let timing = settings.resolve(settings.clear, None);
+    let stagger = timing.stagger * i as u32;
+    let end_translation = transform.translation + Vec3::new(0.0, 5.0, 0.0);
+
+    let idle_tween = Tween::new(
+        timing.ease,
+        stagger,
+        TransformPositionLens {
+            start: transform.translation,
+            end: transform.translation,
+        },
+    );
+
+    let tween1 = Tween::new(
+        timing.ease,
+        timing.duration,
+        TransformPositionLens {
+            start: transform.translation,
+            end: end_translation,
+        },
+    );
+
+    card_index.set_zone(entity, None);
+
+    commands
+        .entity(entity)
+        .remove::<Deck>()
+        .remove::<Hand>()
+        .remove::<CardOnTable>()
+        .insert(Despawning {
+            timer: Timer::new(stagger + timing.duration, TimerMode::Once),
+        });
+    animate(
+        commands,
+        entity,
+        TweenAnim::new(idle_tween.then(tween1)),
+        transform.with_translation(end_translation),
+        settings,
+    );
+}
+
+/// Despawn a card together with its face and back, freeing the meshes and materials created
+/// for it in [`spawn_deck_cards`].
+fn despawn_card<T>(
+    commands: &mut Commands,
+    card_entity: Entity,
+    children: Option<&Children>,
+    q_card_parts: &CardParts,
+    assets: &mut CardAssets,
+    card_index: &mut CardIndex<T>,
+) {
+    let parts = std::iter::once(card_entity).chain(children.into_iter().flatten().copied());
+    for part in parts {
+        assets.free(q_card_parts, part);
+    }
+
+    card_index.remove(card_entity);
+    commands.entity(card_entity).despawn();
+}
+
+fn spawn_deck_cards<T>(
+    commands: &mut Commands,
+    assets: &mut CardAssets,
+    deck_transform: &Transform,
+    deck_area: &DeckArea,
+    card_deck: &[T],
+    card_index: &mut CardIndex<T>,
+) where
+    T: Send + Clone + Sync + Debug + CardMetadata + 'static,
+{
+    let deck_translation = deck_transform.translation;
+    let deck_rotation = deck_transform.rotation;
+
+    for (i, card) in card_deck.iter().enumerate() {
+        let transform =
+            Transform::from_translation(deck_translation + Vec3::new(0.0, 0.01 * (i as f32), 0.0))
+                .with_rotation(
+                    deck_rotation
+                        * Quat::from_rotation_x(std::f32::consts::PI)
+                        * Quat::from_rotation_y(std::f32::consts::PI),
+                );
+
+        // Draw Deck
+        let card_entity = commands
+            .spawn((
+                Name::new("Card"),
+                Card {
+                    pickable: false,
+                    transform: Some(transform),
+                    data: card.clone(),
+                },
+                Deck {
+                    marker: deck_area.marker,
+                },
+                Pickable::default(),
+                transform,
+            ))
+            .observe(on_card_over)
+            .observe(on_card_out)
+            .observe(on_card_click)
+            .observe(on_card_pressed)
+            .observe(on_peeked_card_drop)
+            .id();
+
+        assets.dress(commands, card_entity, card);
+
+        let card_id = card_index.insert(
+            card_entity,
+            Some(CardZone::Deck {
+                marker: deck_area.marker,
+            }),
+            card.key(),
+        );
+        commands.entity(card_entity).insert(card_id);
     }
 }
 
-fn on_card_click(click: On<Pointer<Click>>, mut ew_card: MessageWriter<CardPress>) {
+fn on_card_click(
+    click: On<Pointer<Click>>,
+    mut ew_card: MessageWriter<CardPress>,
+    mut long_press: ResMut<LongPress>,
+) {
+    // the click that ends an inspecting press does not select the card
+    if long_press.take_inspected(click.event().entity) {
+        return;
+    }
     ew_card.write(CardPress {
         entity: click.event().entity,
     });
@@ -887,36 +1822,206 @@ fn on_card_out(click: On<Pointer<Out>>, mut ew_card: MessageWriter<CardOut>) {
 
 pub fn handle_align_cards_in_hand<T>(
     mut commands: Commands,
-    mut cards_in_hand: Query<(Entity, &mut Card<T>, &Hand, &mut Transform)>,
+    mut cards_in_hand: Query<(
+        Entity,
+        &mut Card<T>,
+        &Hand,
+        &mut Transform,
+        Option<&Selected>,
+    )>,
+    q_hand_areas: Query<(&Transform, &HandArea), Without<Card<T>>>,
     mut er_align_cards_in_hand: MessageReader<AlignCardsInHand>,
+    mut ew_error: MessageWriter<LaMesaError>,
+    mut queue: ResMut<OperationQueue>,
+    mut pending: Local<Vec<Queued<AlignCardsInHand>>>,
+    time: Res<Time>,
+    settings: Res<AnimationSettings>,
+    selection_settings: Res<SelectionSettings>,
 ) where
     T: Send + Clone + Sync + Debug + CardMetadata + 'static,
 {
+    let now = time.elapsed();
     for event in er_align_cards_in_hand.read() {
+        let zones = vec![CardZone::Hand {
+            player: event.player,
+        }];
+        pending.push(queue.enqueue(zones, event.clone()));
+    }
+
+    while let Some(Queued {
+        message: event,
+        zones,
+        ..
+    }) = queue.next_ready(&mut pending, now)
+    {
+        let Some(hand_transform) = q_hand_areas
+            .iter()
+            .find(|(_, hand_area)| hand_area.player == event.player)
+            .map(|(transform, _)| *transform)
+        else {
+            ew_error.write(LaMesaError::UnknownHand {
+                player: event.player,
+            });
+            continue;
+        };
+
+        let timing = settings.resolve(settings.align, event.timing);
         let mut cards = cards_in_hand
             .iter_mut()
-            .filter(|(_, _, hand, _)| hand.player == event.player)
+            .filter(|(_, _, hand, _, _)| hand.player == event.player)
             .collect::<Vec<_>>();
-        cards.sort_by(|a, b| a.3.translation.x.partial_cmp(&b.3.translation.x).unwrap());
-
-        // animate x position change
-        for (i, (entity, card, _, transform)) in cards.iter_mut().enumerate() {
-            let original_translation = transform.translation;
-            let mut new_translation = original_translation;
-            new_translation.x = i as f32 * 2.6 - DECK_WIDTH / 2.0;
+        // keep the order the cards have along the hand
+        let along_hand = |transform: &Transform| {
+            (hand_transform.rotation.inverse()
+                * (transform.translation - hand_transform.translation))
+                .x
+        };
+        cards.sort_by(|a, b| along_hand(&a.3).partial_cmp(&along_hand(&b.3)).unwrap());
+
+        for (i, (entity, card, _, transform, selected)) in cards.iter_mut().enumerate() {
+            let slot = hand_slot(&hand_transform, i);
+            let new_translation = slot.translation + raise(*selected, &selection_settings);
 
             let tween = Tween::new(
-                EaseFunction::QuadraticIn,
-                Duration::from_millis(75),
+                timing.ease,
+                timing.duration,
                 TransformPositionLens {
-                    start: original_translation,
+                    start: transform.translation,
                     end: new_translation,
                 },
             );
 
-            card.transform = Some(Transform::from_translation(new_translation));
+            card.transform = Some(Transform::from_translation(slot.translation));
 
-            commands.entity(*entity).insert(TweenAnim::new(tween));
+            animate(
+                &mut commands,
+                *entity,
+                TweenAnim::new(tween),
+                transform.with_translation(new_translation),
+                &settings,
+            );
         }
+
+        queue.hold(&zones, now + timing.duration);
+    }
+}
+
+/// Resting position of the card in `slot` of a hand, spread along the hand's own x axis so
+/// every seat fans its cards the way it faces.
+fn hand_slot(hand_transform: &Transform, slot: usize) -> Transform {
+    let offset = Vec3::new(slot as f32 * 2.6 - DECK_WIDTH / 2.0, 0.0, 0.0);
+    Transform {
+        translation: hand_transform.translation + hand_transform.rotation * offset,
+        rotation: hand_transform.rotation,
+        scale: Vec3::ONE,
     }
 }
+
+/// Number of cards in a player's hand, including the ones still flying there.
+fn cards_in_hand<T>(card_index: &CardIndex<T>, player: usize) -> usize {
+    card_index
+        .iter()
+        .filter(|(_, entry)| entry.zone == Some(CardZone::Hand { player }))
+        .count()
+}
+
+/// Fly a card from its deck to `end` in a player's hand; the card joins the hand once it lands.
+fn fly_to_hand<T>(
+    commands: &mut Commands,
+    card_index: &mut CardIndex<T>,
+    entity: Entity,
+    card: &Card<T>,
+    start: Transform,
+    end: Transform,
+    player: usize,
+    delay: Duration,
+    timing: &AnimationTiming,
+    settings: &AnimationSettings,
+) where
+    T: Send + Clone + Sync + 'static,
+{
+    let end = end.with_scale(start.scale);
+    let seq = flight(start, end, delay, timing, settings.flight_path);
+
+    let card = Card::<T> {
+        pickable: true,
+        transform: Some(Transform::from_translation(end.translation)),
+        data: card.data.clone(),
+    };
+
+    card_index.set_zone(entity, Some(CardZone::Hand { player }));
+
+    commands.entity(entity).remove::<Deck>().insert(card);
+    animate(commands, entity, TweenAnim::new(seq), end, settings);
+
+    if settings.instant {
+        commands.entity(entity).insert(Hand { player });
+    } else {
+        let pause = (delay + timing.duration).as_secs_f32();
+        commands.spawn_task(move || async move {
+            AsyncWorld.sleep(pause).await;
+            AsyncWorld.entity(entity).insert(Hand { player })?;
+
+            Ok(())
+        });
+    }
+}
+
+/// Hover style of the zone a card is in. Hands lift their cards towards the player unless
+/// they say otherwise, tables and decks do not react.
+fn hover_style(
+    zone: CardZone,
+    hand_areas: &Query<(&HandArea, Option<&HoverStyle>)>,
+    play_areas: &Query<(&PlayArea, Option<&HoverStyle>)>,
+    deck_areas: &Query<(&DeckArea, Option<&HoverStyle>)>,
+) -> HoverStyle {
+    let style = match zone {
+        CardZone::Hand { player } => {
+            let style = hand_areas
+                .iter()
+                .find(|(hand, _)| hand.player == player)
+                .and_then(|(_, style)| style.copied());
+            return style.unwrap_or(HoverStyle::Lift(match player {
+                1 => Vec3::new(0., 0.7 / 3.0, 0.7 / 3.0),
+                _ => Vec3::new(0., 0.7 / 3.0, 0.0),
+            }));
+        }
+        CardZone::Table { marker, player } => play_areas
+            .iter()
+            .find(|(area, _)| area.marker == marker && area.player == player)
+            .and_then(|(_, style)| style.copied()),
+        CardZone::Deck { marker } => deck_areas
+            .iter()
+            .find(|(area, _)| area.marker == marker)
+            .and_then(|(_, style)| style.copied()),
+    };
+
+    style.unwrap_or(HoverStyle::None)
+}
+
+/// Remember where restacked deck cards come to rest, so the deck order does not depend on
+/// how far their animations got.
+fn rest_on_deck<T>(
+    query_cards: &mut Query<(Entity, &mut Card<T>, &Transform, &Deck)>,
+    resting: Vec<(Entity, Transform)>,
+) where
+    T: Send + Sync + 'static,
+{
+    for (entity, transform) in resting {
+        if let Ok((_, mut card, _, _)) = query_cards.get_mut(entity) {
+            card.transform = Some(transform);
+        }
+    }
+}
+
+/// Zones touched by an operation on `deck_entity`; empty if it is not a deck area, in which
+/// case the operation runs right away and reports the error.
+pub(crate) fn deck_zones(deck_areas: &Query<&DeckArea>, deck_entity: Entity) -> Vec<CardZone> {
+    deck_areas
+        .get(deck_entity)
+        .map(|deck_area| CardZone::Deck {
+            marker: deck_area.marker,
+        })
+        .into_iter()
+        .collect()
+}
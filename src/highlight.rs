@@ -0,0 +1,178 @@
+use bevy::prelude::*;
+#[cfg(feature = "render")]
+use std::collections::HashSet;
+
+#[cfg(feature = "render")]
+use crate::CardFace;
+
+#[cfg(feature = "render")]
+const CARD_SIZE: Vec2 = Vec2::new(2.5, 3.5);
+#[cfg(feature = "render")]
+const DIM: f32 = 0.35;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HighlightMode {
+    /// Make the face glow; `intensity` scales the emitted light.
+    Glow { intensity: f32 },
+    /// Draw a border of `width` around the card.
+    Outline { width: f32 },
+    /// Multiply the face by the highlight color.
+    Tint,
+}
+
+/// Visual emphasis applied to the face of a card while the component is present.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Highlight {
+    pub color: Color,
+    pub mode: HighlightMode,
+    /// Pulse frequency in Hz, if the highlight should pulse.
+    pub pulse: Option<f32>,
+}
+
+impl Highlight {
+    pub fn glow(color: Color) -> Self {
+        Self {
+            color,
+            mode: HighlightMode::Glow { intensity: 2.0 },
+            pulse: None,
+        }
+    }
+
+    pub fn outline(color: Color) -> Self {
+        Self {
+            color,
+            mode: HighlightMode::Outline { width: 0.1 },
+            pulse: None,
+        }
+    }
+
+    pub fn tint(color: Color) -> Self {
+        Self {
+            color,
+            mode: HighlightMode::Tint,
+            pulse: None,
+        }
+    }
+
+    pub fn pulsing(mut self, frequency: f32) -> Self {
+        self.pulse = Some(frequency);
+        self
+    }
+}
+
+/// Darkens the face of a card, e.g. to show that it cannot be played.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Dimmed;
+
+/// Border mesh spawned behind the face of a card with an outline [`Highlight`].
+#[cfg(feature = "render")]
+#[derive(Component)]
+pub struct CardOutline {
+    width: f32,
+}
+
+/// Needs the `render` feature; without it the components are kept but nothing is drawn.
+#[cfg(feature = "render")]
+pub fn handle_card_highlight(
+    mut commands: Commands,
+    q_changed: Query<Entity, Or<(Changed<Highlight>, Changed<Dimmed>)>>,
+    q_highlights: Query<(Entity, &Highlight)>,
+    mut removed_highlights: RemovedComponents<Highlight>,
+    mut removed_dimmed: RemovedComponents<Dimmed>,
+    q_cards: Query<(Option<&Highlight>, Has<Dimmed>, &Children)>,
+    q_faces: Query<&MeshMaterial3d<StandardMaterial>, With<CardFace>>,
+    q_outlines: Query<(&CardOutline, &Mesh3d, &MeshMaterial3d<StandardMaterial>)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    time: Res<Time>,
+) {
+    // pulsing highlights are updated every frame, the others only when they change
+    let mut entities: HashSet<Entity> = q_changed.iter().collect();
+    entities.extend(removed_highlights.read());
+    entities.extend(removed_dimmed.read());
+    entities.extend(
+        q_highlights
+            .iter()
+            .filter(|(_, highlight)| highlight.pulse.is_some())
+            .map(|(entity, _)| entity),
+    );
+
+    for entity in entities {
+        let Ok((highlight, dimmed, children)) = q_cards.get(entity) else {
+            continue;
+        };
+
+        let strength = highlight.and_then(|h| h.pulse).map_or(1.0, |frequency| {
+            0.7 + 0.3 * (time.elapsed_secs() * std::f32::consts::TAU * frequency).sin()
+        });
+        let color = highlight.map_or(LinearRgba::WHITE, |h| h.color.to_linear());
+
+        // face
+        if let Some(material) = children
+            .iter()
+            .find_map(|child| q_faces.get(child).ok())
+            .and_then(|face| materials.get_mut(&face.0))
+        {
+            let mut base_color = match highlight.map(|h| h.mode) {
+                Some(HighlightMode::Tint) => color,
+                _ => LinearRgba::WHITE,
+            };
+            if dimmed {
+                base_color = LinearRgba::new(
+                    base_color.red * DIM,
+                    base_color.green * DIM,
+                    base_color.blue * DIM,
+                    base_color.alpha,
+                );
+            }
+            material.base_color = base_color.into();
+            material.emissive = match highlight.map(|h| h.mode) {
+                Some(HighlightMode::Glow { intensity }) => color * (intensity * strength),
+                _ => LinearRgba::BLACK,
+            };
+        }
+
+        // outline
+        let width = match highlight.map(|h| h.mode) {
+            Some(HighlightMode::Outline { width }) => Some(width),
+            _ => None,
+        };
+        let outline = children
+            .iter()
+            .find_map(|child| q_outlines.get(child).ok().map(|outline| (child, outline)));
+
+        match (outline, width) {
+            (Some((_, (card_outline, _, material))), Some(width))
+                if card_outline.width == width =>
+            {
+                if let Some(material) = materials.get_mut(&material.0) {
+                    material.base_color = (color * strength).into();
+                }
+            }
+            (outline, width) => {
+                if let Some((child, (_, mesh, material))) = outline {
+                    meshes.remove(mesh);
+                    materials.remove(material);
+                    commands.entity(child).despawn();
+                }
+                if let Some(width) = width {
+                    let size = CARD_SIZE + Vec2::splat(2.0 * width);
+                    let mesh = meshes.add(Plane3d::default().mesh().size(size.x, size.y));
+                    let material = materials.add(StandardMaterial {
+                        base_color: (color * strength).into(),
+                        unlit: true,
+                        ..Default::default()
+                    });
+                    commands.entity(entity).with_children(|parent| {
+                        parent.spawn((
+                            CardOutline { width },
+                            Mesh3d(mesh),
+                            MeshMaterial3d(material),
+                            Transform::from_xyz(0.0, -0.002, 0.0),
+                        ));
+                    });
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use bevy::picking::pointer::{PointerAction, PointerInput};
+use bevy::prelude::*;
+use std::{fmt::Debug, time::Duration};
+
+#[cfg(feature = "render")]
+use crate::visibility::FaceHidden;
+#[cfg(feature = "render")]
+use crate::{Card, CardFace};
+
+/// Show an enlarged copy of a face-up card in front of the camera, unless its face is hidden
+/// from the local player. Sent by the plugin on
+/// right-click or long-press; inspecting the card that is already shown dismisses it.
+#[derive(Message, Clone)]
+pub struct InspectCard {
+    pub card_entity: Entity,
+}
+
+/// Hide the inspected card and show the original again.
+#[derive(Message, Clone)]
+pub struct DismissInspect;
+
+#[derive(Message, Clone, Debug)]
+pub struct CardInspected {
+    pub card_entity: Entity,
+}
+
+#[derive(Message, Clone, Debug)]
+pub struct InspectDismissed {
+    pub card_entity: Entity,
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct InspectSettings {
+    /// How long the primary button has to be held on a card to inspect it.
+    pub long_press: Duration,
+    /// Distance of the enlarged copy from the camera.
+    pub distance: f32,
+    pub scale: f32,
+}
+
+impl Default for InspectSettings {
+    fn default() -> Self {
+        Self {
+            long_press: Duration::from_millis(500),
+            distance: 5.0,
+            scale: 1.0,
+        }
+    }
+}
+
+/// Card being inspected, if any.
+#[derive(Resource, Default)]
+pub struct Inspection {
+    current: Option<(Entity, Entity)>,
+}
+
+impl Inspection {
+    pub fn card(&self) -> Option<Entity> {
+        self.current.map(|(card, _)| card)
+    }
+}
+
+/// Enlarged copy of an inspected card.
+#[derive(Component)]
+pub struct InspectedCopy;
+
+/// Card the primary button went down on, and when.
+#[derive(Resource, Default)]
+pub struct LongPress {
+    pressed: Option<(Entity, Duration)>,
+    /// Card the button that is still down asked to inspect.
+    requested: Option<Entity>,
+    /// Card inspected or dismissed by the button that is still down; releasing it over the
+    /// card does not count as a click.
+    inspected: Option<Entity>,
+}
+
+impl LongPress {
+    /// The press on `entity`, if it is still down, inspected or dismissed the card: a refused
+    /// inspection leaves the click to the card.
+    #[cfg(feature = "render")]
+    fn acted_on(&mut self, entity: Entity) {
+        if self.requested == Some(entity) {
+            self.inspected = Some(entity);
+        }
+    }
+
+    /// Whether a click on `entity` ends the press that inspected it.
+    pub(crate) fn take_inspected(&mut self, entity: Entity) -> bool {
+        self.inspected
+            .take_if(|inspected| *inspected == entity)
+            .is_some()
+    }
+}
+
+pub(crate) fn on_card_pressed(
+    press: On<Pointer<Press>>,
+    mut ew_inspect: MessageWriter<InspectCard>,
+    mut long_press: ResMut<LongPress>,
+    time: Res<Time>,
+) {
+    let entity = press.event().entity;
+    match press.event().button {
+        PointerButton::Secondary => {
+            long_press.requested = Some(entity);
+            ew_inspect.write(InspectCard {
+                card_entity: entity,
+            });
+        }
+        PointerButton::Primary => long_press.pressed = Some((entity, time.elapsed())),
+        _ => {}
+    }
+}
+
+#[cfg(feature = "render")]
+fn on_inspected_click(_click: On<Pointer<Click>>, mut ew_dismiss: MessageWriter<DismissInspect>) {
+    ew_dismiss.write(DismissInspect);
+}
+
+pub fn handle_long_press(
+    mut long_press: ResMut<LongPress>,
+    mut er_pointer: MessageReader<PointerInput>,
+    mut ew_inspect: MessageWriter<InspectCard>,
+    time: Res<Time>,
+    settings: Res<InspectSettings>,
+) {
+    // the button may come up anywhere, not only over the card it went down on
+    for input in er_pointer.read() {
+        if matches!(
+            input.action,
+            PointerAction::Release(_) | PointerAction::Cancel
+        ) {
+            long_press.pressed = None;
+            long_press.requested = None;
+            long_press.inspected = None;
+        }
+    }
+
+    if let Some((entity, since)) = long_press.pressed {
+        if time.elapsed() - since >= settings.long_press {
+            long_press.pressed = None;
+            long_press.requested = Some(entity);
+            ew_inspect.write(InspectCard {
+                card_entity: entity,
+            });
+        }
+    }
+}
+
+/// Needs the `render` feature, the copy shares the face material of the card.
+#[cfg(feature = "render")]
+pub fn handle_inspect_card<T>(
+    mut commands: Commands,
+    mut er_inspect: MessageReader<InspectCard>,
+    mut er_dismiss: MessageReader<DismissInspect>,
+    mut inspection: ResMut<Inspection>,
+    mut long_press: ResMut<LongPress>,
+    q_cards: Query<(&Transform, &Children, Has<FaceHidden>), With<Card<T>>>,
+    q_faces: Query<&MeshMaterial3d<StandardMaterial>, With<CardFace>>,
+    q_copies: Query<&Mesh3d, With<InspectedCopy>>,
+    q_camera: Query<&GlobalTransform, With<Camera3d>>,
+    mut q_visibility: Query<&mut Visibility>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut ew_inspected: MessageWriter<CardInspected>,
+    mut ew_dismissed: MessageWriter<InspectDismissed>,
+    settings: Res<InspectSettings>,
+) where
+    T: Send + Clone + Sync + Debug + 'static,
+{
+    if er_dismiss.read().count() > 0 {
+        dismiss(
+            &mut commands,
+            &mut inspection,
+            &q_copies,
+            &mut q_visibility,
+            &mut meshes,
+            &mut ew_dismissed,
+        );
+    }
+
+    for inspect in er_inspect.read() {
+        let toggled = inspection.card() == Some(inspect.card_entity);
+        dismiss(
+            &mut commands,
+            &mut inspection,
+            &q_copies,
+            &mut q_visibility,
+            &mut meshes,
+            &mut ew_dismissed,
+        );
+        if toggled {
+            long_press.acted_on(inspect.card_entity);
+            continue;
+        }
+
+        let Ok((transform, children, hidden)) = q_cards.get(inspect.card_entity) else {
+            continue;
+        };
+        if hidden {
+            continue;
+        }
+        let Ok(camera) = q_camera.single() else {
+            continue;
+        };
+
+        // only face-up cards can be inspected
+        let to_camera = camera.translation() - transform.translation;
+        if (transform.rotation * Vec3::Y).dot(to_camera) <= 0.0 {
+            continue;
+        }
+        let Some(face) = children.iter().find_map(|child| q_faces.get(child).ok()) else {
+            continue;
+        };
+
+        // face towards the camera, top of the card up
+        let (forward, up, right) = (*camera.forward(), *camera.up(), *camera.right());
+        let copy = commands
+            .spawn((
+                Name::new("Inspected card"),
+                InspectedCopy,
+                Mesh3d(meshes.add(Plane3d::default().mesh().size(2.5, 3.5))),
+                MeshMaterial3d(face.0.clone()),
+                Transform {
+                    translation: camera.translation() + forward * settings.distance,
+                    rotation: Quat::from_mat3(&Mat3::from_cols(right, -forward, -up)),
+                    scale: Vec3::splat(settings.scale),
+                },
+            ))
+            .observe(on_inspected_click)
+            .id();
+
+        if let Ok(mut visibility) = q_visibility.get_mut(inspect.card_entity) {
+            *visibility = Visibility::Hidden;
+        }
+        inspection.current = Some((inspect.card_entity, copy));
+        long_press.acted_on(inspect.card_entity);
+        ew_inspected.write(CardInspected {
+            card_entity: inspect.card_entity,
+        });
+    }
+}
+
+#[cfg(feature = "render")]
+fn dismiss(
+    commands: &mut Commands,
+    inspection: &mut Inspection,
+    q_copies: &Query<&Mesh3d, With<InspectedCopy>>,
+    q_visibility: &mut Query<&mut Visibility>,
+    meshes: &mut Assets<Mesh>,
+    ew_dismissed: &mut MessageWriter<InspectDismissed>,
+) {
+    let Some((card, copy)) = inspection.current.take() else {
+        return;
+    };
+
+    if let Ok(mesh) = q_copies.get(copy) {
+        meshes.remove(mesh);
+    }
+    commands.entity(copy).despawn();
+    if let Ok(mut visibility) = q_visibility.get_mut(card) {
+        *visibility = Visibility::Inherited;
+    }
+    ew_dismissed.write(InspectDismissed { card_entity: card });
+}
@@ -1,15 +1,52 @@
+pub mod ai;
+pub mod animation;
+pub mod attach;
+pub mod counters;
 pub mod events;
+pub mod highlight;
+pub mod inspect;
+pub mod peek;
+pub mod queue;
+pub mod replay;
+pub mod rules;
+pub mod selection;
+pub mod shuffle;
+pub mod sync;
+pub mod table;
+pub mod testing;
+pub mod turn;
+pub mod visibility;
 
+use animation::AnimationSettings;
+use attach::{handle_attach_card, handle_detach_card, handle_follow_host, AttachCard, DetachCard};
+use bevy::picking::pointer::PointerInput;
 use bevy::prelude::*;
+use bevy::transform::TransformSystems;
 use bevy_tweening::TweeningPlugin;
+use counters::{handle_counter_badges, BadgeSettings};
 use events::*;
-use std::{fmt::Debug, marker::PhantomData};
+#[cfg(feature = "render")]
+use highlight::handle_card_highlight;
+use inspect::*;
+use peek::*;
+use queue::OperationQueue;
+use rules::{handle_playable_cards, TableRules};
+use selection::*;
+use serde::{Deserialize, Serialize};
+use shuffle::TableRng;
+use std::{collections::HashMap, fmt::Debug, marker::PhantomData};
+use visibility::{handle_card_visibility, LocalPlayer};
 
 pub trait CardMetadata {
     type Output;
 
     fn front_image_filename(&self) -> String;
     fn back_image_filename(&self) -> String;
+
+    /// Optional game-defined key used to look the card up in [`CardIndex`].
+    fn key(&self) -> Option<String> {
+        None
+    }
 }
 
 #[derive(Component)]
@@ -51,6 +88,194 @@ pub struct CardOnTable {
     pub player: usize,
 }
 
+/// How cards react to the pointer, set on a [`HandArea`], [`PlayArea`] or [`DeckArea`].
+///
+/// Only the top card of a deck reacts. Without this component hands lift their cards and the
+/// other zones do nothing.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub enum HoverStyle {
+    None,
+    /// Move the card by this offset.
+    Lift(Vec3),
+    /// Scale the card up by this factor.
+    Scale(f32),
+    /// Turn the card towards the camera, 1.0 facing it completely.
+    Tilt(f32),
+}
+
+/// Card under the pointer, with the transform it returns to once the pointer leaves.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Hovered {
+    pub zone: CardZone,
+    pub rest: Transform,
+}
+
+/// Child entity showing the face of a card.
+#[derive(Component)]
+pub struct CardFace;
+
+/// Child entity showing the back of a card.
+#[derive(Component)]
+pub struct CardBack;
+
+/// Child entity showing the back of a card on the face side, while the face is hidden.
+#[derive(Component)]
+pub struct CardCover;
+
+/// Card that is being animated away and will be despawned once the timer finishes.
+#[derive(Component)]
+pub struct Despawning {
+    pub timer: Timer,
+}
+
+/// Stable identifier assigned to every card when its deck is rendered.
+#[derive(
+    Component, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub struct CardId(pub u64);
+
+/// Zone a card currently belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CardZone {
+    Deck { marker: usize },
+    Hand { player: usize },
+    Table { marker: usize, player: usize },
+}
+
+#[derive(Clone, Debug)]
+pub struct CardIndexEntry {
+    pub entity: Entity,
+    pub zone: Option<CardZone>,
+    pub key: Option<String>,
+}
+
+/// Lookup of rendered cards by [`CardId`] or by [`CardMetadata::key`].
+///
+/// Zones are updated as soon as an operation is accepted, so a card that is still flying
+/// towards a hand is already reported as being in that hand.
+#[derive(Resource)]
+pub struct CardIndex<T> {
+    next_id: u64,
+    entries: HashMap<CardId, CardIndexEntry>,
+    ids: HashMap<Entity, CardId>,
+    keys: HashMap<String, CardId>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for CardIndex<T> {
+    fn default() -> Self {
+        Self {
+            next_id: 0,
+            entries: HashMap::new(),
+            ids: HashMap::new(),
+            keys: HashMap::new(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> CardIndex<T> {
+    pub fn get(&self, id: CardId) -> Option<&CardIndexEntry> {
+        self.entries.get(&id)
+    }
+
+    pub fn entity(&self, id: CardId) -> Option<Entity> {
+        self.entries.get(&id).map(|entry| entry.entity)
+    }
+
+    pub fn zone(&self, id: CardId) -> Option<CardZone> {
+        self.entries.get(&id).and_then(|entry| entry.zone)
+    }
+
+    pub fn id_of(&self, entity: Entity) -> Option<CardId> {
+        self.ids.get(&entity).copied()
+    }
+
+    pub fn zone_of(&self, entity: Entity) -> Option<CardZone> {
+        self.id_of(entity).and_then(|id| self.zone(id))
+    }
+
+    pub fn by_key(&self, key: &str) -> Option<CardId> {
+        self.keys.get(key).copied()
+    }
+
+    pub fn entity_by_key(&self, key: &str) -> Option<Entity> {
+        self.by_key(key).and_then(|id| self.entity(id))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&CardId, &CardIndexEntry)> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub(crate) fn insert(
+        &mut self,
+        entity: Entity,
+        zone: Option<CardZone>,
+        key: Option<String>,
+    ) -> CardId {
+        let id = CardId(self.next_id);
+        self.next_id += 1;
+
+        if let Some(key) = &key {
+            self.keys.insert(key.clone(), id);
+        }
+        self.ids.insert(entity, id);
+        self.entries
+            .insert(id, CardIndexEntry { entity, zone, key });
+
+        id
+    }
+
+    pub(crate) fn set_zone(&mut self, entity: Entity, zone: Option<CardZone>) {
+        if let Some(entry) = self
+            .ids
+            .get(&entity)
+            .and_then(|id| self.entries.get_mut(id))
+        {
+            entry.zone = zone;
+        }
+    }
+
+    pub(crate) fn set_key(&mut self, entity: Entity, key: Option<String>) {
+        let Some(id) = self.ids.get(&entity).copied() else {
+            return;
+        };
+        let Some(entry) = self.entries.get_mut(&id) else {
+            return;
+        };
+
+        if let Some(old) = entry.key.take() {
+            if self.keys.get(&old) == Some(&id) {
+                self.keys.remove(&old);
+            }
+        }
+        if let Some(key) = &key {
+            self.keys.insert(key.clone(), id);
+        }
+        entry.key = key;
+    }
+
+    pub(crate) fn remove(&mut self, entity: Entity) {
+        let Some(id) = self.ids.remove(&entity) else {
+            return;
+        };
+
+        if let Some(key) = self.entries.remove(&id).and_then(|entry| entry.key) {
+            if self.keys.get(&key) == Some(&id) {
+                self.keys.remove(&key);
+            }
+        }
+    }
+}
+
 #[derive(Default, Resource)]
 pub struct LaMesaPluginSettings {
     pub num_players: usize,
@@ -61,37 +286,139 @@ pub struct LaMesaPlugin<T: Send + Clone + Sync + Debug + CardMetadata + 'static>
     pub PhantomData<T>,
 );
 
+impl<T: Send + Clone + Sync + Debug + CardMetadata + 'static> LaMesaPlugin<T> {
+    /// The plugin for apps that do not render, see [`HeadlessLaMesaPlugin`].
+    pub fn headless() -> HeadlessLaMesaPlugin<T> {
+        HeadlessLaMesaPlugin(PhantomData)
+    }
+}
+
+/// [`LaMesaPlugin`] for apps without rendering, e.g. under `MinimalPlugins`: cards get no
+/// meshes and every operation completes instantly.
+#[derive(Default)]
+pub struct HeadlessLaMesaPlugin<T: Send + Clone + Sync + Debug + CardMetadata + 'static>(
+    pub PhantomData<T>,
+);
+
 impl<T: Send + Clone + Sync + Debug + CardMetadata + 'static> Plugin for LaMesaPlugin<T> {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, handle_render_deck::<T>)
-            .add_systems(
-                Update,
-                (
-                    handle_align_cards_in_hand::<T>,
-                    handle_card_hover::<T>,
-                    handle_card_out::<T>,
-                    handle_deck_shuffle::<T>,
-                    handle_discard_card_to_deck::<T>,
-                    handle_draw_to_hand::<T>,
-                    handle_draw_to_table::<T>,
-                    handle_place_card_on_table::<T>,
-                    handle_render_deck::<T>,
-                )
-                    .chain(),
-            )
-            .add_plugins(TweeningPlugin)
-            .add_message::<AlignCardsInHand>()
-            .add_message::<CardHover>()
-            .add_message::<CardOut>()
-            .add_message::<CardPress>()
-            .add_message::<DeckRendered>()
-            .add_message::<DeckShuffle>()
-            .add_message::<DiscardCardToDeck>()
-            .add_message::<DrawToHand>()
-            .add_message::<DrawToTable>()
-            .add_message::<PlaceCardOnTable>()
-            .add_message::<RenderDeck<T>>();
+        app.add_plugins(TweeningPlugin);
+        build_table::<T>(app);
     }
 }
 
+impl<T: Send + Clone + Sync + Debug + CardMetadata + 'static> Plugin for HeadlessLaMesaPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(AnimationSettings {
+            instant: true,
+            ..default()
+        });
+        build_table::<T>(app);
+    }
+}
+
+/// Systems, messages and resources shared by [`LaMesaPlugin`] and [`HeadlessLaMesaPlugin`].
+fn build_table<T: Send + Clone + Sync + Debug + CardMetadata + 'static>(app: &mut App) {
+    app.add_systems(
+        Update,
+        // operations sent in the same frame are queued in the order of these handlers, see
+        // `OperationQueue`
+        (
+            handle_align_cards_in_hand::<T>,
+            handle_card_hover::<T>,
+            handle_card_out::<T>,
+            handle_deck_shuffle::<T>,
+            handle_deal::<T>,
+            handle_reorder_deck::<T>,
+            handle_discard_card_to_deck::<T>,
+            handle_draw_to_hand::<T>,
+            handle_draw_to_table::<T>,
+            (
+                handle_place_card_on_table::<T>,
+                handle_attach_card::<T>,
+                handle_detach_card,
+            ),
+            handle_render_deck::<T>,
+            handle_replace_deck::<T>,
+            handle_clear_deck::<T>,
+            handle_despawn_all_cards::<T>,
+            handle_card_despawn::<T>,
+            handle_card_selection::<T>,
+            (
+                handle_playable_cards::<T>.run_if(resource_exists::<TableRules<T>>),
+                #[cfg(feature = "render")]
+                handle_card_highlight.run_if(resource_exists::<Assets<StandardMaterial>>),
+            )
+                .chain(),
+            (
+                handle_reveal_card::<T>,
+                #[cfg(feature = "render")]
+                handle_reveal_face::<T>.after(handle_reveal_card::<T>),
+                handle_card_visibility::<T>,
+                handle_counter_badges.run_if(resource_exists::<Assets<Font>>),
+            ),
+            (
+                handle_long_press.run_if(resource_exists::<Messages<PointerInput>>),
+                #[cfg(feature = "render")]
+                handle_inspect_card::<T>.run_if(resource_exists::<Assets<Mesh>>),
+            )
+                .chain(),
+            (
+                release_stale_peeks,
+                handle_peek_deck::<T>,
+                handle_peek_reorder::<T>,
+                handle_resolve_peek::<T>,
+            )
+                .chain(),
+        )
+            .chain(),
+    )
+    .add_systems(
+        PostUpdate,
+        handle_follow_host::<T>.before(TransformSystems::Propagate),
+    )
+    .init_resource::<CardIndex<T>>()
+    .init_resource::<OperationQueue>()
+    .init_resource::<AnimationSettings>()
+    .init_resource::<SelectionSettings>()
+    .init_resource::<InspectSettings>()
+    .init_resource::<Inspection>()
+    .init_resource::<LongPress>()
+    .init_resource::<LocalPlayer>()
+    .init_resource::<TableRng>()
+    .init_resource::<BadgeSettings>()
+    .add_message::<AlignCardsInHand>()
+    .add_message::<AttachCard>()
+    .add_message::<CardHover>()
+    .add_message::<CardInspected>()
+    .add_message::<CardOut>()
+    .add_message::<CardPress>()
+    .add_message::<ClearDeck>()
+    .add_message::<ClearSelection>()
+    .add_message::<Deal>()
+    .add_message::<DeckRendered>()
+    .add_message::<DeckReordered>()
+    .add_message::<DeckShuffle>()
+    .add_message::<DeckShuffled>()
+    .add_message::<DespawnAllCards>()
+    .add_message::<DetachCard>()
+    .add_message::<DismissInspect>()
+    .add_message::<DiscardCardToDeck>()
+    .add_message::<DrawToHand>()
+    .add_message::<DrawToTable>()
+    .add_message::<InspectCard>()
+    .add_message::<InspectDismissed>()
+    .add_message::<LaMesaError>()
+    .add_message::<MovePeekedCard>()
+    .add_message::<PeekDeck>()
+    .add_message::<PeekResult>()
+    .add_message::<PlaceCardOnTable>()
+    .add_message::<RenderDeck<T>>()
+    .add_message::<ResolvePeek>()
+    .add_message::<RevealCard<T>>()
+    .add_message::<ReorderDeck<T>>()
+    .add_message::<ReplaceDeck<T>>()
+    .add_message::<SelectionChanged>();
+}
+
 pub const DECK_WIDTH: f32 = 5.0 * 2.6;
@@ -0,0 +1,411 @@
+use bevy::prelude::*;
+use bevy_tweening::TweenAnim;
+use std::{fmt::Debug, time::Duration};
+
+use crate::animation::{animate, flight, restack, AnimationSettings, AnimationTiming, Restack};
+use crate::events::{deck_zones, CardPress, LaMesaError};
+use crate::queue::{OperationQueue, Queued};
+use crate::{Card, CardIndex, CardZone, Deck, DeckArea, HandArea};
+
+/// Lift the top `count` cards of a deck and fan them face up towards `player`.
+///
+/// The deck stays busy until [`ResolvePeek`] puts the cards back, or until the deck is cleared,
+/// replaced or every card despawned. Pressing two fanned cards one after the other swaps them;
+/// dropping a fanned card onto another one moves it to that card's place.
+#[derive(Message, Clone)]
+pub struct PeekDeck {
+    pub deck_entity: Entity,
+    pub count: usize,
+    pub player: usize,
+    pub timing: Option<AnimationTiming>,
+}
+
+/// Move a peeked card to `position` in the fan, 0 being the card that goes back on top.
+#[derive(Message, Clone)]
+pub struct MovePeekedCard {
+    pub deck_entity: Entity,
+    pub card_entity: Entity,
+    pub position: usize,
+}
+
+/// Put the peeked cards back: `bottom` under the deck, first entry lowest, the others on top
+/// in fan order.
+#[derive(Message, Clone)]
+pub struct ResolvePeek {
+    pub deck_entity: Entity,
+    pub bottom: Vec<Entity>,
+}
+
+/// Where the peeked cards went: `top` top card first, `bottom` lowest card first.
+#[derive(Message, Clone, Debug)]
+pub struct PeekResult {
+    pub deck_entity: Entity,
+    pub player: usize,
+    pub top: Vec<Entity>,
+    pub bottom: Vec<Entity>,
+}
+
+/// Added to a deck area while its top cards are being peeked at.
+#[derive(Component, Clone, Debug)]
+pub struct Peeking {
+    pub player: usize,
+    /// Peeked cards in fan order, the card that goes back on top first.
+    pub cards: Vec<Entity>,
+    /// Card pressed first when swapping two cards.
+    pub selected: Option<Entity>,
+    fan: Transform,
+    face_down: Quat,
+    timing: AnimationTiming,
+    zones: Vec<CardZone>,
+}
+
+impl Peeking {
+    /// Resting transform of the card at `position` in the fan.
+    fn slot(&self, position: usize) -> Transform {
+        let center = (self.cards.len() as f32 - 1.0) / 2.0;
+        let mut offset = Vec3::new((position as f32 - center) * 2.6, 0.0, 0.0);
+        if self.selected == Some(self.cards[position]) {
+            offset.y += 0.3;
+        }
+
+        Transform {
+            translation: self.fan.translation + self.fan.rotation * offset,
+            rotation: self.fan.rotation,
+            scale: Vec3::ONE,
+        }
+    }
+
+    /// Move every card to its place in the fan.
+    fn lay_out<T: Send + Sync + 'static>(
+        &self,
+        commands: &mut Commands,
+        transforms: &Query<&Transform, With<Card<T>>>,
+        settings: &AnimationSettings,
+    ) {
+        for (position, entity) in self.cards.iter().enumerate() {
+            let Ok(start) = transforms.get(*entity) else {
+                continue;
+            };
+            let end = self.slot(position).with_scale(start.scale);
+            let seq = flight(
+                *start,
+                end,
+                self.timing.stagger * position as u32,
+                &self.timing,
+                settings.flight_path,
+            );
+            animate(commands, *entity, TweenAnim::new(seq), end, settings);
+        }
+    }
+}
+
+pub fn handle_peek_deck<T>(
+    mut commands: Commands,
+    mut er_peek: MessageReader<PeekDeck>,
+    q_cards: Query<(Entity, &Transform, &Deck), With<Card<T>>>,
+    q_transforms: Query<&Transform, With<Card<T>>>,
+    q_decks: Query<(&Transform, &DeckArea), Without<Deck>>,
+    q_hand_areas: Query<(&Transform, &HandArea)>,
+    deck_areas: Query<&DeckArea>,
+    mut ew_error: MessageWriter<LaMesaError>,
+    mut queue: ResMut<OperationQueue>,
+    mut pending: Local<Vec<Queued<PeekDeck>>>,
+    time: Res<Time>,
+    settings: Res<AnimationSettings>,
+) where
+    T: Send + Clone + Sync + Debug + 'static,
+{
+    let now = time.elapsed();
+    for peek in er_peek.read() {
+        let zones = deck_zones(&deck_areas, peek.deck_entity);
+        pending.push(queue.enqueue(zones, peek.clone()));
+    }
+
+    while let Some(Queued {
+        message: peek,
+        zones,
+        ..
+    }) = queue.next_ready(&mut pending, now)
+    {
+        let Ok((deck_transform, deck_area)) = q_decks.get(peek.deck_entity) else {
+            ew_error.write(LaMesaError::UnknownDeck {
+                deck_entity: peek.deck_entity,
+            });
+            continue;
+        };
+        let Some((hand_transform, _)) = q_hand_areas
+            .iter()
+            .find(|(_, hand)| hand.player == peek.player)
+        else {
+            ew_error.write(LaMesaError::UnknownHand {
+                player: peek.player,
+            });
+            continue;
+        };
+
+        // top cards of the deck, top card first
+        let mut cards: Vec<(Entity, &Transform)> = q_cards
+            .iter()
+            .filter(|(_, _, deck)| deck.marker == deck_area.marker)
+            .map(|(entity, transform, _)| (entity, transform))
+            .collect();
+        cards.sort_by(|a, b| b.1.translation.y.partial_cmp(&a.1.translation.y).unwrap());
+        cards.truncate(peek.count);
+
+        // fan halfway between the deck and the seat, facing the seat
+        let mut center = deck_transform
+            .translation
+            .lerp(hand_transform.translation, 0.5);
+        center.y = 2.0;
+
+        let peeking = Peeking {
+            player: peek.player,
+            cards: cards.iter().map(|(entity, _)| *entity).collect(),
+            selected: None,
+            fan: Transform::from_translation(center).with_rotation(hand_transform.rotation),
+            face_down: cards
+                .first()
+                .map_or(deck_transform.rotation, |(_, transform)| transform.rotation),
+            timing: settings.resolve(settings.peek, peek.timing),
+            zones: zones.clone(),
+        };
+
+        peeking.lay_out(&mut commands, &q_transforms, &settings);
+        commands.entity(peek.deck_entity).insert(peeking);
+
+        // nothing else touches the deck until the peek is resolved
+        queue.lock(&zones, peek.deck_entity);
+    }
+}
+
+/// Unlock the decks whose peek is over without being resolved, e.g. because the deck area was
+/// despawned.
+pub fn release_stale_peeks(mut queue: ResMut<OperationQueue>, q_peeking: Query<(), With<Peeking>>) {
+    for owner in queue.lock_owners() {
+        if !q_peeking.contains(owner) {
+            queue.unlock(owner);
+        }
+    }
+}
+
+/// Drop the peek on `deck_entity` without putting the cards back, for operations that are
+/// about to despawn them.
+pub(crate) fn cancel_peek(
+    commands: &mut Commands,
+    queue: &mut OperationQueue,
+    deck_entity: Entity,
+) {
+    commands.entity(deck_entity).remove::<Peeking>();
+    queue.unlock(deck_entity);
+}
+
+/// Dropping a fanned card onto another one of the same fan moves it to that card's place.
+pub(crate) fn on_peeked_card_drop(
+    drop: On<Pointer<DragDrop>>,
+    q_peeking: Query<(Entity, &Peeking)>,
+    mut ew_move: MessageWriter<MovePeekedCard>,
+) {
+    let (target, dropped) = (drop.event().entity, drop.dropped);
+    let Some((deck_entity, position)) = q_peeking.iter().find_map(|(deck_entity, peeking)| {
+        let position = peeking.cards.iter().position(|e| *e == target)?;
+        peeking
+            .cards
+            .contains(&dropped)
+            .then_some((deck_entity, position))
+    }) else {
+        return;
+    };
+
+    ew_move.write(MovePeekedCard {
+        deck_entity,
+        card_entity: dropped,
+        position,
+    });
+}
+
+pub fn handle_peek_reorder<T>(
+    mut commands: Commands,
+    mut er_press: MessageReader<CardPress>,
+    mut er_move: MessageReader<MovePeekedCard>,
+    mut q_peeking: Query<&mut Peeking>,
+    q_transforms: Query<&Transform, With<Card<T>>>,
+    mut ew_error: MessageWriter<LaMesaError>,
+    settings: Res<AnimationSettings>,
+) where
+    T: Send + Clone + Sync + Debug + 'static,
+{
+    for press in er_press.read() {
+        let Some(mut peeking) = q_peeking
+            .iter_mut()
+            .find(|peeking| peeking.cards.contains(&press.entity))
+        else {
+            continue;
+        };
+
+        // first press selects a card, the second one swaps it with the pressed card
+        match peeking.selected {
+            None => peeking.selected = Some(press.entity),
+            Some(selected) if selected == press.entity => peeking.selected = None,
+            Some(selected) => {
+                let a = peeking.cards.iter().position(|e| *e == selected).unwrap();
+                let b = peeking
+                    .cards
+                    .iter()
+                    .position(|e| *e == press.entity)
+                    .unwrap();
+                peeking.cards.swap(a, b);
+                peeking.selected = None;
+            }
+        }
+
+        peeking.lay_out(&mut commands, &q_transforms, &settings);
+    }
+
+    for event in er_move.read() {
+        let Ok(mut peeking) = q_peeking.get_mut(event.deck_entity) else {
+            ew_error.write(LaMesaError::NotPeeking {
+                deck_entity: event.deck_entity,
+            });
+            continue;
+        };
+        let Some(position) = peeking.cards.iter().position(|e| *e == event.card_entity) else {
+            ew_error.write(LaMesaError::UnknownCard {
+                card_entity: event.card_entity,
+            });
+            continue;
+        };
+
+        let card = peeking.cards.remove(position);
+        let position = event.position.min(peeking.cards.len());
+        peeking.cards.insert(position, card);
+        peeking.selected = None;
+
+        peeking.lay_out(&mut commands, &q_transforms, &settings);
+    }
+}
+
+pub fn handle_resolve_peek<T>(
+    mut commands: Commands,
+    mut er_resolve: MessageReader<ResolvePeek>,
+    q_peeking: Query<(&Peeking, &Transform, &DeckArea)>,
+    q_cards: Query<(Entity, &Transform, &Deck), With<Card<T>>>,
+    mut q_resting: Query<&mut Card<T>>,
+    card_index: Res<CardIndex<T>>,
+    mut ew_error: MessageWriter<LaMesaError>,
+    mut ew_result: MessageWriter<PeekResult>,
+    mut queue: ResMut<OperationQueue>,
+    time: Res<Time>,
+    settings: Res<AnimationSettings>,
+) where
+    T: Send + Clone + Sync + Debug + 'static,
+{
+    for resolve in er_resolve.read() {
+        let Ok((peeking, deck_transform, deck_area)) = q_peeking.get(resolve.deck_entity) else {
+            ew_error.write(LaMesaError::NotPeeking {
+                deck_entity: resolve.deck_entity,
+            });
+            continue;
+        };
+        if let Some(card_entity) = resolve
+            .bottom
+            .iter()
+            .find(|entity| !peeking.cards.contains(entity))
+        {
+            ew_error.write(LaMesaError::CardNotInZone {
+                card_entity: *card_entity,
+                zone: card_index.zone_of(*card_entity),
+            });
+            continue;
+        }
+        // every peeked card goes back exactly once
+        if let Some((_, card_entity)) = resolve
+            .bottom
+            .iter()
+            .enumerate()
+            .find(|(i, entity)| resolve.bottom[..*i].contains(entity))
+        {
+            ew_error.write(LaMesaError::DuplicateCard {
+                card_entity: *card_entity,
+            });
+            continue;
+        }
+
+        let top: Vec<Entity> = peeking
+            .cards
+            .iter()
+            .filter(|entity| !resolve.bottom.contains(entity))
+            .copied()
+            .collect();
+
+        // rest of the deck, bottom card first
+        let mut rest: Vec<(Entity, &Transform)> = q_cards
+            .iter()
+            .filter(|(entity, _, deck)| {
+                deck.marker == deck_area.marker && !peeking.cards.contains(entity)
+            })
+            .map(|(entity, transform, _)| (entity, transform))
+            .collect();
+        rest.sort_by(|a, b| a.1.translation.y.partial_cmp(&b.1.translation.y).unwrap());
+
+        // peeked cards land one at a time, face down again; the rest of the deck only moves up
+        // to make room for the cards put under it
+        let land = |k: usize, entity: &Entity| {
+            let (_, start, _) = q_cards.get(*entity).ok()?;
+            Some(Restack {
+                entity: *entity,
+                start: *start,
+                before: Duration::ZERO,
+                path: vec![],
+                after: peeking.timing.stagger * k as u32,
+                rotation: Some(peeking.face_down),
+            })
+        };
+        let mut plan: Vec<Restack> = resolve
+            .bottom
+            .iter()
+            .enumerate()
+            .filter_map(|(k, entity)| land(k, entity))
+            .collect();
+        plan.extend(rest.iter().map(|(entity, start)| Restack {
+            entity: *entity,
+            start: **start,
+            before: Duration::ZERO,
+            path: vec![],
+            after: Duration::ZERO,
+            rotation: None,
+        }));
+        plan.extend(
+            top.iter()
+                .rev()
+                .enumerate()
+                .filter_map(|(k, entity)| land(resolve.bottom.len() + k, entity)),
+        );
+
+        let mut deck_translation = deck_transform.translation;
+        deck_translation.y = 0.0;
+        let (total, resting) = restack(
+            &mut commands,
+            plan,
+            deck_translation,
+            &peeking.timing,
+            &settings,
+        );
+        for (entity, transform) in resting {
+            if let Ok(mut card) = q_resting.get_mut(entity) {
+                card.transform = Some(transform);
+            }
+        }
+
+        let now = time.elapsed();
+        queue.unlock(resolve.deck_entity);
+        queue.hold(&peeking.zones, now + total);
+
+        ew_result.write(PeekResult {
+            deck_entity: resolve.deck_entity,
+            player: peeking.player,
+            top,
+            bottom: resolve.bottom.clone(),
+        });
+        commands.entity(resolve.deck_entity).remove::<Peeking>();
+    }
+}
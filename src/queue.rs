@@ -0,0 +1,140 @@
+use bevy::prelude::*;
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use crate::CardZone;
+
+/// Serializes table operations that touch the same zones.
+///
+/// Every operation takes a ticket for the zones it touches when its message is read. It only
+/// starts once it is first in line for all of them and the animation of the previous operation
+/// on those zones has finished. With `interrupt` set, operations start immediately and the new
+/// tweens replace whatever was playing.
+///
+/// Tickets follow the order the plugin reads its messages in, not the order they were sent
+/// in: within a frame every [`DeckShuffle`](crate::events::DeckShuffle) is queued before any
+/// [`DrawToHand`](crate::events::DrawToHand), so a draw sent before a shuffle in the same
+/// frame draws from the shuffled deck. Send them in separate frames to keep their order.
+#[derive(Resource, Default)]
+pub struct OperationQueue {
+    pub interrupt: bool,
+    next_ticket: u64,
+    waiting: HashMap<CardZone, VecDeque<u64>>,
+    busy_until: HashMap<CardZone, Duration>,
+    locked: HashMap<CardZone, Entity>,
+}
+
+/// Message waiting in a handler for its turn in the [`OperationQueue`].
+pub struct Queued<M> {
+    pub ticket: u64,
+    pub zones: Vec<CardZone>,
+    pub message: M,
+}
+
+impl OperationQueue {
+    /// Whether an animation started by an earlier operation is still playing on `zone`.
+    pub fn is_busy(&self, zone: CardZone, now: Duration) -> bool {
+        self.locked.contains_key(&zone)
+            || self
+                .busy_until
+                .get(&zone)
+                .is_some_and(|busy_until| *busy_until > now)
+    }
+
+    /// Whether any operation is waiting for or animating on `zone`.
+    pub fn is_pending(&self, zone: CardZone, now: Duration) -> bool {
+        self.is_busy(zone, now) || self.waiting.get(&zone).is_some_and(|w| !w.is_empty())
+    }
+
+    /// Whether no operation is waiting or animating anywhere on the table.
+    pub fn is_idle(&self, now: Duration) -> bool {
+        self.locked.is_empty()
+            && self.waiting.values().all(|w| w.is_empty())
+            && self
+                .busy_until
+                .values()
+                .all(|busy_until| *busy_until <= now)
+    }
+
+    /// Every zone an operation has waited for or animated on.
+    pub(crate) fn zones(&self) -> impl Iterator<Item = CardZone> + '_ {
+        self.waiting
+            .keys()
+            .chain(self.busy_until.keys())
+            .chain(self.locked.keys())
+            .copied()
+    }
+
+    pub(crate) fn enqueue<M>(&mut self, zones: Vec<CardZone>, message: M) -> Queued<M> {
+        let ticket = self.next_ticket;
+        self.next_ticket += 1;
+
+        for zone in zones.iter() {
+            self.waiting.entry(*zone).or_default().push_back(ticket);
+        }
+
+        Queued {
+            ticket,
+            zones,
+            message,
+        }
+    }
+
+    /// Remove and return the first pending operation that is allowed to start.
+    pub(crate) fn next_ready<M>(
+        &mut self,
+        pending: &mut Vec<Queued<M>>,
+        now: Duration,
+    ) -> Option<Queued<M>> {
+        let position = pending.iter().position(|queued| {
+            self.interrupt
+                || queued.zones.iter().all(|zone| {
+                    !self.is_busy(*zone, now)
+                        && self.waiting.get(zone).and_then(|waiting| waiting.front())
+                            == Some(&queued.ticket)
+                })
+        })?;
+
+        let queued = pending.remove(position);
+        for zone in queued.zones.iter() {
+            if let Some(waiting) = self.waiting.get_mut(zone) {
+                waiting.retain(|ticket| *ticket != queued.ticket);
+            }
+            if self.interrupt {
+                self.busy_until.remove(zone);
+            }
+        }
+
+        Some(queued)
+    }
+
+    /// Keep `zones` busy until the animation of the operation that just started is over.
+    pub(crate) fn hold(&mut self, zones: &[CardZone], until: Duration) {
+        for zone in zones.iter() {
+            let busy_until = self.busy_until.entry(*zone).or_default();
+            *busy_until = (*busy_until).max(until);
+        }
+    }
+
+    /// Keep `zones` busy until [`unlock`](Self::unlock) is called for `owner`, for operations
+    /// that wait on the player rather than on an animation.
+    pub(crate) fn lock(&mut self, zones: &[CardZone], owner: Entity) {
+        for zone in zones.iter() {
+            self.busy_until.remove(zone);
+            self.locked.insert(*zone, owner);
+        }
+    }
+
+    /// Let operations on the zones locked by `owner` start again.
+    pub(crate) fn unlock(&mut self, owner: Entity) {
+        self.locked.retain(|_, locked_by| *locked_by != owner);
+    }
+
+    /// Entities holding a [`lock`](Self::lock) on some zone.
+    pub(crate) fn lock_owners(&self) -> Vec<Entity> {
+        let mut owners: Vec<Entity> = self.locked.values().copied().collect();
+        owners.sort();
+        owners.dedup();
+        owners
+    }
+}
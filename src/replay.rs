@@ -0,0 +1,455 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Debug,
+    fs, io,
+    marker::PhantomData,
+    path::Path,
+    time::Duration,
+};
+
+use crate::attach::{AttachCard, DetachCard};
+use crate::events::{
+    handle_align_cards_in_hand, AlignCardsInHand, ClearDeck, Deal, DeckReordered, DeckShuffle,
+    DespawnAllCards, DiscardCardToDeck, DrawToHand, DrawToTable, PlaceCardOnTable, RenderDeck,
+    ReorderDeck, ReplaceDeck,
+};
+use crate::peek::{MovePeekedCard, PeekDeck, ResolvePeek};
+use crate::shuffle::TableRng;
+use crate::sync::{apply, DeckReorder, TableCommand, TableWriters};
+use crate::table::TableQuery;
+use crate::{CardId, CardMetadata, DeckArea};
+
+/// Deck list rendered on the deck area with this marker.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReplayDeck<T> {
+    pub deck: usize,
+    pub cards: Vec<T>,
+}
+
+/// Command and when it was written, counted from the start of the recording.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReplayEntry<T> {
+    pub at: Duration,
+    pub command: TableCommand<T>,
+}
+
+/// Everything needed to play a match again: the decks rendered before the first command, the
+/// seed of the [`TableRng`] and the zone operations in the order they were queued.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Replay<T> {
+    pub seed: u64,
+    pub decks: Vec<ReplayDeck<T>>,
+    pub commands: Vec<ReplayEntry<T>>,
+}
+
+impl<T: Serialize + DeserializeOwned> Replay<T> {
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, json)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let json = fs::read_to_string(path)?;
+        serde_json::from_str(&json).map_err(io::Error::other)
+    }
+}
+
+/// Records the table while present. Insert it before the decks are rendered and before the
+/// [`TableRng`] is first used, since the replay only keeps its seed.
+#[derive(Resource)]
+pub struct ReplayRecorder<T> {
+    replay: Replay<T>,
+    start: Option<Duration>,
+    /// Entries of the reorders not handled yet on every deck, oldest first.
+    reorders: HashMap<Entity, VecDeque<usize>>,
+}
+
+impl<T> Default for ReplayRecorder<T> {
+    fn default() -> Self {
+        Self {
+            replay: Replay {
+                seed: 0,
+                decks: vec![],
+                commands: vec![],
+            },
+            start: None,
+            reorders: HashMap::new(),
+        }
+    }
+}
+
+impl<T> ReplayRecorder<T> {
+    pub fn replay(&self) -> &Replay<T> {
+        &self.replay
+    }
+}
+
+/// Plays a [`Replay`] back through the plugin. The decks are rendered by the player, so the
+/// game should not render its own while replaying.
+#[derive(Resource)]
+pub struct ReplayPlayer<T> {
+    replay: Replay<T>,
+    clock: Duration,
+    next: usize,
+    started: bool,
+    step: bool,
+    pub paused: bool,
+    /// Playback speed, 1.0 being the recorded speed.
+    pub speed: f32,
+}
+
+impl<T> ReplayPlayer<T> {
+    pub fn new(replay: Replay<T>) -> Self {
+        Self {
+            replay,
+            clock: Duration::ZERO,
+            next: 0,
+            started: false,
+            step: false,
+            paused: false,
+            speed: 1.0,
+        }
+    }
+
+    /// Play the next command right away, even while paused.
+    pub fn step(&mut self) {
+        self.step = true;
+    }
+
+    pub fn finished(&self) -> bool {
+        self.next >= self.replay.commands.len()
+    }
+}
+
+#[derive(SystemParam)]
+pub struct DeckReaders<'w, 's, T>
+where
+    T: Send + Clone + Sync + Debug + CardMetadata + 'static,
+{
+    render: MessageReader<'w, 's, RenderDeck<T>>,
+    replace: MessageReader<'w, 's, ReplaceDeck<T>>,
+    clear: MessageReader<'w, 's, ClearDeck>,
+    despawn_all: MessageReader<'w, 's, DespawnAllCards>,
+    shuffle: MessageReader<'w, 's, DeckShuffle>,
+    reorder: MessageReader<'w, 's, ReorderDeck<T>>,
+    reordered: MessageReader<'w, 's, DeckReordered>,
+    peek: MessageReader<'w, 's, PeekDeck>,
+    move_peeked: MessageReader<'w, 's, MovePeekedCard>,
+    resolve_peek: MessageReader<'w, 's, ResolvePeek>,
+}
+
+#[derive(SystemParam)]
+pub struct MoveReaders<'w, 's> {
+    draw_to_hand: MessageReader<'w, 's, DrawToHand>,
+    draw_to_table: MessageReader<'w, 's, DrawToTable>,
+    deal: MessageReader<'w, 's, Deal>,
+    place: MessageReader<'w, 's, PlaceCardOnTable>,
+    discard: MessageReader<'w, 's, DiscardCardToDeck>,
+    align: MessageReader<'w, 's, AlignCardsInHand>,
+    attach: MessageReader<'w, 's, AttachCard>,
+    detach: MessageReader<'w, 's, DetachCard>,
+}
+
+#[derive(SystemParam)]
+pub struct TableReaders<'w, 's, T>
+where
+    T: Send + Clone + Sync + Debug + CardMetadata + 'static,
+{
+    decks: DeckReaders<'w, 's, T>,
+    moves: MoveReaders<'w, 's>,
+}
+
+pub fn handle_replay_recording<T>(
+    mut recorder: ResMut<ReplayRecorder<T>>,
+    mut readers: TableReaders<T>,
+    rng: Res<TableRng>,
+    deck_areas: Query<&DeckArea>,
+    table: TableQuery<T>,
+    time: Res<Time>,
+) where
+    T: Send + Clone + Sync + Debug + CardMetadata + 'static,
+{
+    let recorder = &mut *recorder;
+    if recorder.start.is_none() {
+        recorder.replay.seed = rng.seed();
+    }
+    let start = *recorder.start.get_or_insert(time.elapsed());
+    let at = time.elapsed() - start;
+
+    let marker = |deck_entity: Entity| deck_areas.get(deck_entity).ok().map(|area| area.marker);
+    let id = |card_entity: Entity| table.card_id(card_entity);
+    let ids = |card_entities: &[Entity]| -> Option<Vec<CardId>> {
+        card_entities.iter().map(|entity| id(*entity)).collect()
+    };
+    let decks = &mut readers.decks;
+    let moves = &mut readers.moves;
+
+    // read in the order the plugin handles the messages, which is the order they are queued in
+    let mut commands = vec![];
+    for align in moves.align.read() {
+        commands.push(TableCommand::AlignCardsInHand {
+            player: align.player,
+        });
+    }
+    for shuffle in decks.shuffle.read() {
+        commands.extend(
+            marker(shuffle.deck_entity).map(|deck| TableCommand::DeckShuffle {
+                deck,
+                style: shuffle.style,
+                seed: shuffle.seed,
+            }),
+        );
+    }
+    for deal in moves.deal.read() {
+        commands.extend(marker(deal.deck_entity).map(|deck| TableCommand::Deal {
+            deck,
+            players: deal.players.clone(),
+            cards_each: deal.cards_each,
+            order: deal.order,
+        }));
+    }
+    // the order a reorder leaves the deck in is only known once it is handled, so it holds
+    // its place with an order that changes nothing until then; this also saves sorting by
+    // key, whose key functions cannot be saved
+    for reorder in decks.reorder.read() {
+        let Some(deck) = marker(reorder.deck_entity) else {
+            continue;
+        };
+        recorder
+            .reorders
+            .entry(reorder.deck_entity)
+            .or_default()
+            .push_back(recorder.replay.commands.len() + commands.len());
+        commands.push(TableCommand::ReorderDeck {
+            deck,
+            op: DeckReorder::Order(vec![]),
+        });
+    }
+    for discard in moves.discard.read() {
+        if let (Some(card), Some(deck)) = (id(discard.card_entity), marker(discard.deck_entity)) {
+            commands.push(TableCommand::DiscardCardToDeck { card, deck });
+        }
+    }
+    for draw in moves.draw_to_hand.read() {
+        commands.extend(
+            marker(draw.deck_entity).map(|deck| TableCommand::DrawToHand {
+                deck,
+                num_cards: draw.num_cards,
+                player: draw.player,
+            }),
+        );
+    }
+    for draw in moves.draw_to_table.read() {
+        commands.extend(
+            marker(draw.deck_entity).map(|deck| TableCommand::DrawToTable {
+                deck,
+                play_area_markers: draw.play_area_markers.clone(),
+                player: draw.player,
+            }),
+        );
+    }
+    for place in moves.place.read() {
+        commands.extend(
+            id(place.card_entity).map(|card| TableCommand::PlaceCardOnTable {
+                card,
+                marker: place.marker,
+                player: place.player,
+            }),
+        );
+    }
+    for attach in moves.attach.read() {
+        if let (Some(card), Some(host)) = (id(attach.card_entity), id(attach.host_entity)) {
+            commands.push(TableCommand::AttachCard {
+                card,
+                host,
+                side: attach.side,
+            });
+        }
+    }
+    for detach in moves.detach.read() {
+        commands.extend(id(detach.card_entity).map(|card| TableCommand::DetachCard { card }));
+    }
+    // decks rendered before anything else happens are rendered first when playing back, the
+    // others when their turn comes
+    for render in decks.render.read() {
+        let Some(deck) = marker(render.deck_entity) else {
+            continue;
+        };
+        let replay = &mut recorder.replay;
+        if replay.commands.is_empty()
+            && commands.is_empty()
+            && replay.decks.iter().all(|rendered| rendered.deck != deck)
+        {
+            replay.decks.push(ReplayDeck {
+                deck,
+                cards: render.deck.clone(),
+            });
+        } else {
+            commands.push(TableCommand::RenderDeck {
+                deck,
+                cards: render.deck.clone(),
+            });
+        }
+    }
+    for replace in decks.replace.read() {
+        commands.extend(
+            marker(replace.deck_entity).map(|deck| TableCommand::ReplaceDeck {
+                deck,
+                cards: replace.deck.clone(),
+            }),
+        );
+    }
+    for clear in decks.clear.read() {
+        commands.extend(
+            marker(clear.deck_entity).map(|deck| TableCommand::ClearDeck {
+                deck,
+                animate: clear.animate,
+            }),
+        );
+    }
+    for despawn in decks.despawn_all.read() {
+        commands.push(TableCommand::DespawnAllCards {
+            animate: despawn.animate,
+        });
+    }
+    for peek in decks.peek.read() {
+        commands.extend(marker(peek.deck_entity).map(|deck| TableCommand::PeekDeck {
+            deck,
+            count: peek.count,
+            player: peek.player,
+        }));
+    }
+    for moved in decks.move_peeked.read() {
+        if let (Some(deck), Some(card)) = (marker(moved.deck_entity), id(moved.card_entity)) {
+            commands.push(TableCommand::MovePeekedCard {
+                deck,
+                card,
+                position: moved.position,
+            });
+        }
+    }
+    for resolve in decks.resolve_peek.read() {
+        if let (Some(deck), Some(bottom)) = (marker(resolve.deck_entity), ids(&resolve.bottom)) {
+            commands.push(TableCommand::ResolvePeek { deck, bottom });
+        }
+    }
+
+    recorder.replay.commands.extend(
+        commands
+            .into_iter()
+            .map(|command| ReplayEntry { at, command }),
+    );
+
+    for reordered in decks.reordered.read() {
+        let Some(index) = recorder
+            .reorders
+            .get_mut(&reordered.deck_entity)
+            .and_then(|waiting| waiting.pop_front())
+        else {
+            continue;
+        };
+        if let (
+            Some(order),
+            Some(ReplayEntry {
+                command: TableCommand::ReorderDeck { op, .. },
+                ..
+            }),
+        ) = (
+            ids(&reordered.cards),
+            recorder.replay.commands.get_mut(index),
+        ) {
+            *op = DeckReorder::Order(order);
+        }
+    }
+}
+
+pub fn handle_replay_playback<T>(
+    mut player: ResMut<ReplayPlayer<T>>,
+    mut rng: ResMut<TableRng>,
+    table: TableQuery<T>,
+    mut writers: TableWriters<T>,
+    time: Res<Time>,
+) where
+    T: Send + Clone + Sync + Debug + CardMetadata + 'static,
+{
+    let player = &mut *player;
+
+    // render the decks first, the cards need to exist before any command refers to them
+    if !player.started {
+        player.started = true;
+        *rng = TableRng::seeded(player.replay.seed);
+        for deck in player.replay.decks.iter() {
+            let render = TableCommand::RenderDeck {
+                deck: deck.deck,
+                cards: deck.cards.clone(),
+            };
+            apply(render, &table, &mut writers);
+        }
+        return;
+    }
+
+    if player.step {
+        player.step = false;
+        if let Some(entry) = player.replay.commands.get(player.next) {
+            player.clock = player.clock.max(entry.at);
+        }
+    } else if !player.paused {
+        player.clock += time.delta().mul_f32(player.speed.max(0.0));
+    }
+
+    while let Some(entry) = player.replay.commands.get(player.next) {
+        if entry.at > player.clock {
+            break;
+        }
+        let command = entry.command.clone();
+        player.next += 1;
+        let resets_deck = matches!(
+            command,
+            TableCommand::RenderDeck { .. }
+                | TableCommand::ReplaceDeck { .. }
+                | TableCommand::ClearDeck { .. }
+                | TableCommand::DespawnAllCards { .. }
+        );
+        apply(command, &table, &mut writers);
+
+        // the plugin handles these in a fixed order within a frame, so the commands after one
+        // wait for the next frame to keep the order they were recorded in
+        if resets_deck {
+            break;
+        }
+    }
+}
+
+/// Records the table into a [`ReplayRecorder`] and plays back a [`ReplayPlayer`], whichever
+/// of the two resources is present.
+pub struct ReplayPlugin<T>(PhantomData<T>);
+
+impl<T> Default for ReplayPlugin<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T> Plugin for ReplayPlugin<T>
+where
+    T: Send + Clone + Sync + Debug + CardMetadata + Serialize + DeserializeOwned + 'static,
+{
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                // read every message in the frame the plugin queues it, the sync plugin applies
+                // its commands before this
+                handle_replay_recording::<T>
+                    .run_if(resource_exists::<ReplayRecorder<T>>)
+                    .before(handle_align_cards_in_hand::<T>),
+                handle_replay_playback::<T>
+                    .run_if(resource_exists::<ReplayPlayer<T>>)
+                    .before(handle_align_cards_in_hand::<T>),
+            ),
+        );
+    }
+}
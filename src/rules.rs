@@ -0,0 +1,155 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use std::fmt::Debug;
+
+use crate::highlight::Dimmed;
+use crate::table::TableQuery;
+use crate::{Card, CardIndex, DeckArea, HandArea, PlayArea};
+
+/// Game rules consulted before the plugin moves a card on behalf of a player. Every method
+/// allows the move unless overridden.
+pub trait CardRules<T: Send + Sync + 'static>: Send + Sync + 'static {
+    /// Whether `card` may be placed on `area`. Cards in hand that cannot be played on any of
+    /// their player's play areas are [`Dimmed`].
+    fn can_play(&self, _card: &T, _area: &PlayArea, _table: &TableQuery<T>) -> bool {
+        true
+    }
+
+    /// Whether `player` may draw from `deck`, to their hand or to the table.
+    fn can_draw(&self, _player: usize, _deck: &DeckArea, _table: &TableQuery<T>) -> bool {
+        true
+    }
+
+    /// Whether `card` may be discarded to `deck`.
+    fn can_discard(&self, _card: &T, _deck: &DeckArea, _table: &TableQuery<T>) -> bool {
+        true
+    }
+}
+
+/// Rules of the game; without this resource every move is allowed.
+#[derive(Resource)]
+pub struct TableRules<T: Send + Sync + 'static>(Box<dyn CardRules<T>>);
+
+impl<T: Send + Sync + 'static> TableRules<T> {
+    pub fn new(rules: impl CardRules<T>) -> Self {
+        Self(Box::new(rules))
+    }
+}
+
+/// [`TableRules`] together with the table they are checked against, for the handlers to
+/// check a move when it starts rather than when it is asked for. Moves that refer to
+/// something missing are allowed here and reported by the handlers.
+#[derive(SystemParam)]
+pub struct RulesCheck<'w, 's, T>
+where
+    T: Send + Sync + 'static,
+{
+    rules: Option<Res<'w, TableRules<T>>>,
+    table: TableQuery<'w, 's, T>,
+    play_areas: Query<'w, 's, &'static PlayArea>,
+    deck_areas: Query<'w, 's, &'static DeckArea>,
+}
+
+impl<'w, 's, T> RulesCheck<'w, 's, T>
+where
+    T: Send + Sync + 'static,
+{
+    pub(crate) fn allows_play(&self, card_entity: Entity, marker: usize, player: usize) -> bool {
+        let Some(rules) = self.rules.as_ref() else {
+            return true;
+        };
+        let area = self
+            .table
+            .play_area_entity(marker, player)
+            .and_then(|entity| self.play_areas.get(entity).ok());
+        match (self.table.data(card_entity), area) {
+            (Some(card), Some(area)) => rules.0.can_play(card, area, &self.table),
+            _ => true,
+        }
+    }
+
+    pub(crate) fn allows_draw(&self, deck_entity: Entity, player: usize) -> bool {
+        let Some(rules) = self.rules.as_ref() else {
+            return true;
+        };
+        self.deck_areas
+            .get(deck_entity)
+            .map_or(true, |deck| rules.0.can_draw(player, deck, &self.table))
+    }
+
+    pub(crate) fn allows_discard(&self, card_entity: Entity, deck_entity: Entity) -> bool {
+        let Some(rules) = self.rules.as_ref() else {
+            return true;
+        };
+        match (
+            self.table.data(card_entity),
+            self.deck_areas.get(deck_entity),
+        ) {
+            (Some(card), Ok(deck)) => rules.0.can_discard(card, deck, &self.table),
+            _ => true,
+        }
+    }
+}
+
+/// Added to cards in hand that no play area accepts; `dimmed` is set when
+/// [`handle_playable_cards`] dimmed the card itself rather than finding it dimmed by the game.
+#[derive(Component)]
+pub struct Unplayable {
+    dimmed: bool,
+}
+
+pub fn handle_playable_cards<T>(
+    mut commands: Commands,
+    rules: Res<TableRules<T>>,
+    table: TableQuery<T>,
+    card_index: Res<CardIndex<T>>,
+    q_hand_areas: Query<&HandArea>,
+    q_play_areas: Query<&PlayArea>,
+    q_unplayable: Query<(Entity, &Unplayable)>,
+    q_dimmed: Query<(), With<Dimmed>>,
+    q_changed: Query<(), Or<(Changed<Card<T>>, Changed<PlayArea>, Changed<HandArea>)>>,
+) where
+    T: Send + Clone + Sync + Debug + 'static,
+{
+    // playability only changes with the rules, the cards or where they are
+    if !rules.is_changed() && !card_index.is_changed() && q_changed.is_empty() {
+        return;
+    }
+
+    let mut unplayable = vec![];
+    for hand in q_hand_areas.iter() {
+        for (entity, card) in table.hand(hand.player) {
+            let playable = q_play_areas
+                .iter()
+                .filter(|area| area.player == hand.player)
+                .any(|area| rules.0.can_play(card, area, &table));
+            if !playable {
+                unplayable.push(entity);
+            }
+        }
+    }
+
+    for (entity, marker) in q_unplayable.iter() {
+        if unplayable.contains(&entity) {
+            continue;
+        }
+        // cards the game dimmed stay dimmed
+        if marker.dimmed {
+            commands.entity(entity).remove::<(Unplayable, Dimmed)>();
+        } else {
+            commands.entity(entity).remove::<Unplayable>();
+        }
+    }
+    for entity in unplayable {
+        if q_unplayable.contains(entity) {
+            continue;
+        }
+        if q_dimmed.contains(entity) {
+            commands.entity(entity).insert(Unplayable { dimmed: false });
+        } else {
+            commands
+                .entity(entity)
+                .insert((Unplayable { dimmed: true }, Dimmed));
+        }
+    }
+}
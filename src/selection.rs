@@ -0,0 +1,205 @@
+use bevy::prelude::*;
+use bevy_tweening::{lens::TransformPositionLens, Tween, TweenAnim};
+use std::{collections::HashSet, fmt::Debug};
+
+use crate::animation::{animate, AnimationSettings};
+use crate::events::CardPress;
+use crate::queue::OperationQueue;
+use crate::{Card, CardIndex, CardZone};
+
+/// Card selected by the player. Only cards in a hand or on the table can be selected, and the
+/// selection is dropped when the card leaves the zone it was selected in.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Selected {
+    pub zone: CardZone,
+    order: u64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SelectionMode {
+    /// Selecting a card deselects the previous one.
+    #[default]
+    Single,
+    /// Cards are toggled independently, up to `SelectionSettings::max`.
+    Multi,
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct SelectionSettings {
+    pub mode: SelectionMode,
+    /// Most cards selected at once in [`SelectionMode::Multi`]; further presses are ignored.
+    pub max: Option<usize>,
+    /// How high selected cards are raised above their resting position.
+    pub raise: f32,
+}
+
+impl Default for SelectionSettings {
+    fn default() -> Self {
+        Self {
+            mode: SelectionMode::Single,
+            max: None,
+            raise: 0.5,
+        }
+    }
+}
+
+/// Deselect every card.
+#[derive(Message, Clone)]
+pub struct ClearSelection;
+
+/// Emitted whenever the selection changes; `selection` lists the selected cards in the order
+/// they were selected.
+#[derive(Message, Clone, Debug)]
+pub struct SelectionChanged {
+    pub added: Vec<Entity>,
+    pub removed: Vec<Entity>,
+    pub selection: Vec<Entity>,
+}
+
+/// Offset of a card from its resting position due to being selected.
+pub(crate) fn raise(selected: Option<&Selected>, settings: &SelectionSettings) -> Vec3 {
+    match selected {
+        Some(_) => Vec3::Y * settings.raise,
+        None => Vec3::ZERO,
+    }
+}
+
+pub fn handle_card_selection<T>(
+    mut commands: Commands,
+    mut er_press: MessageReader<CardPress>,
+    mut er_clear: MessageReader<ClearSelection>,
+    q_cards: Query<(Entity, &Card<T>, &Transform, Option<&Selected>)>,
+    card_index: Res<CardIndex<T>>,
+    mut ew_changed: MessageWriter<SelectionChanged>,
+    mut next_order: Local<u64>,
+    queue: Res<OperationQueue>,
+    time: Res<Time>,
+    settings: Res<SelectionSettings>,
+    animation_settings: Res<AnimationSettings>,
+) where
+    T: Send + Clone + Sync + Debug + 'static,
+{
+    let mut selection: Vec<(Entity, Selected)> = q_cards
+        .iter()
+        .filter_map(|(entity, _, _, selected)| selected.map(|selected| (entity, *selected)))
+        .collect();
+    selection.sort_by_key(|(_, selected)| selected.order);
+    let initial: HashSet<Entity> = selection.iter().map(|(entity, _)| *entity).collect();
+
+    // cards that left their zone are deselected without moving them, their own operation is
+    // animating them
+    let mut moved = HashSet::new();
+    selection.retain(|(entity, selected)| {
+        let keep = card_index.zone_of(*entity) == Some(selected.zone);
+        if !keep {
+            moved.insert(*entity);
+        }
+        keep
+    });
+
+    if er_clear.read().count() > 0 {
+        selection.clear();
+    }
+
+    for press in er_press.read() {
+        let Ok((entity, card, _, _)) = q_cards.get(press.entity) else {
+            continue;
+        };
+        let Some(zone @ (CardZone::Hand { .. } | CardZone::Table { .. })) =
+            card_index.zone_of(entity)
+        else {
+            continue;
+        };
+        if !card.pickable {
+            continue;
+        }
+
+        if let Some(position) = selection.iter().position(|(e, _)| *e == entity) {
+            selection.remove(position);
+            continue;
+        }
+
+        match settings.mode {
+            SelectionMode::Single => selection.clear(),
+            SelectionMode::Multi => {
+                if settings.max.is_some_and(|max| selection.len() >= max) {
+                    continue;
+                }
+            }
+        }
+
+        *next_order += 1;
+        selection.push((
+            entity,
+            Selected {
+                zone,
+                order: *next_order,
+            },
+        ));
+    }
+
+    let added: Vec<Entity> = selection
+        .iter()
+        .map(|(entity, _)| *entity)
+        .filter(|entity| !initial.contains(entity))
+        .collect();
+    let mut removed: Vec<Entity> = initial
+        .iter()
+        .filter(|entity| !selection.iter().any(|(e, _)| e == *entity))
+        .copied()
+        .collect();
+    removed.sort();
+    if added.is_empty() && removed.is_empty() {
+        return;
+    }
+
+    let timing = animation_settings.resolve(animation_settings.select, None);
+    let now = time.elapsed();
+    let lift = |commands: &mut Commands, entity: Entity, offset: Vec3| {
+        let Ok((_, card, transform, _)) = q_cards.get(entity) else {
+            return;
+        };
+        let (Some(resting), Some(zone)) = (card.transform, card_index.zone_of(entity)) else {
+            return;
+        };
+        if queue.is_pending(zone, now) {
+            return;
+        }
+
+        let end_translation = resting.translation + offset;
+        let tween = Tween::new(
+            timing.ease,
+            timing.duration,
+            TransformPositionLens {
+                start: transform.translation,
+                end: end_translation,
+            },
+        );
+        animate(
+            commands,
+            entity,
+            TweenAnim::new(tween),
+            transform.with_translation(end_translation),
+            &animation_settings,
+        );
+    };
+
+    for entity in removed.iter() {
+        commands.entity(*entity).remove::<Selected>();
+        if !moved.contains(entity) {
+            lift(&mut commands, *entity, Vec3::ZERO);
+        }
+    }
+    for (entity, selected) in selection.iter() {
+        if added.contains(entity) {
+            commands.entity(*entity).insert(*selected);
+            lift(&mut commands, *entity, Vec3::Y * settings.raise);
+        }
+    }
+
+    ew_changed.write(SelectionChanged {
+        added,
+        removed,
+        selection: selection.iter().map(|(entity, _)| *entity).collect(),
+    });
+}
@@ -0,0 +1,335 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{cmp::Reverse, time::Duration};
+
+use crate::animation::{AnimationTiming, Restack};
+
+const RIGHT: Vec3 = Vec3::new(0.0, 0.0, -2.6);
+const LEFT: Vec3 = Vec3::new(0.0, 0.0, 2.6);
+
+/// How [`DeckShuffle`](crate::events::DeckShuffle) mixes a deck.
+///
+/// Every style computes the new order of the deck first and then animates the cards into it,
+/// so what the player sees is the order the deck ends up in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ShuffleStyle {
+    /// Random order, cards thrown alternately to either side of the deck and stacked back.
+    #[default]
+    Alternate,
+    /// Split the deck near the middle and interleave the two halves.
+    Riffle,
+    /// Peel small packets off the top onto a new pile, reversing the order of the packets.
+    Overhand,
+    /// Move the top part of the deck under the bottom part.
+    Cut,
+    /// Spread the cards around the deck and gather them back in random order.
+    Wash,
+}
+
+/// Source of randomness for every shuffle on the table. Seed it to make a match repeatable.
+#[derive(Resource, Debug)]
+pub struct TableRng {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl TableRng {
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn rng(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
+}
+
+impl Default for TableRng {
+    fn default() -> Self {
+        Self::seeded(rand::thread_rng().gen())
+    }
+}
+
+impl ShuffleStyle {
+    /// New order of `cards`, both bottom card first, and the way each card gets there.
+    pub(crate) fn plan(
+        self,
+        cards: &[(Entity, Transform)],
+        deck_translation: Vec3,
+        timing: &AnimationTiming,
+        rng: &mut impl Rng,
+    ) -> Vec<Restack> {
+        match self {
+            ShuffleStyle::Alternate => alternate(cards, deck_translation, timing, rng),
+            ShuffleStyle::Riffle => riffle(cards, deck_translation, timing, rng),
+            ShuffleStyle::Overhand => overhand(cards, deck_translation, timing, rng),
+            ShuffleStyle::Cut => cut(cards, deck_translation, timing, rng),
+            ShuffleStyle::Wash => wash(cards, deck_translation, timing, rng),
+        }
+    }
+}
+
+/// Deterministic change to the order of a deck, see
+/// [`ReorderDeck`](crate::events::ReorderDeck).
+#[derive(Clone)]
+pub enum ReorderOp<T> {
+    /// Move the top `n` cards under the rest of the deck.
+    Cut(usize),
+    /// Turn the order of the deck upside down.
+    Reverse,
+    /// Sort the deck by a key computed from the card data, lowest key on top.
+    SortByKey(fn(&T) -> i64),
+    /// Put this card on top of the deck, keeping the order of the others.
+    ToTop(Entity),
+    /// Put these cards on top of the deck in this order, top card first; cards that are not
+    /// listed keep their order under them.
+    Order(Vec<Entity>),
+}
+
+impl<T> ReorderOp<T> {
+    /// Old position of the card at every position of the reordered deck, both bottom card
+    /// first. Fails with the first card the operation refers to that is not in `cards`.
+    pub(crate) fn order(&self, cards: &[(Entity, &T)]) -> Result<Vec<usize>, Entity> {
+        let position = |entity: &Entity| cards.iter().position(|(e, _)| e == entity).ok_or(*entity);
+
+        let n = cards.len();
+        let mut order: Vec<usize> = (0..n).collect();
+        match self {
+            ReorderOp::Cut(count) => order.rotate_right((*count).min(n)),
+            ReorderOp::Reverse => order.reverse(),
+            ReorderOp::SortByKey(key) => order.sort_by_key(|i| Reverse(key(cards[*i].1))),
+            ReorderOp::ToTop(entity) => {
+                let position = position(entity)?;
+                order.remove(position);
+                order.push(position);
+            }
+            ReorderOp::Order(entities) => {
+                let listed = entities
+                    .iter()
+                    .map(position)
+                    .collect::<Result<Vec<_>, _>>()?;
+                order.retain(|i| !listed.contains(i));
+                order.extend(listed.iter().rev());
+            }
+        }
+
+        Ok(order)
+    }
+}
+
+/// Move `cards` (bottom card first) into `order`: cards that change place are lifted aside and
+/// dropped back one at a time, the others stay where they are.
+pub(crate) fn reorder(
+    cards: &[(Entity, Transform)],
+    order: &[usize],
+    deck_translation: Vec3,
+    timing: &AnimationTiming,
+) -> Vec<Restack> {
+    order
+        .iter()
+        .enumerate()
+        .map(|(i, j)| {
+            let (entity, start) = cards[*j];
+            if i == *j {
+                Restack {
+                    entity,
+                    start,
+                    before: Duration::ZERO,
+                    path: vec![],
+                    after: Duration::ZERO,
+                    rotation: None,
+                }
+            } else {
+                Restack {
+                    entity,
+                    start,
+                    before: Duration::ZERO,
+                    path: vec![deck_translation + LEFT + height(*j)],
+                    after: timing.stagger * i as u32,
+                    rotation: None,
+                }
+            }
+        })
+        .collect()
+}
+
+fn height(i: usize) -> Vec3 {
+    Vec3::new(0.0, i as f32 * 0.01, 0.0)
+}
+
+fn alternate(
+    cards: &[(Entity, Transform)],
+    deck_translation: Vec3,
+    timing: &AnimationTiming,
+    rng: &mut impl Rng,
+) -> Vec<Restack> {
+    let mut shuffled = cards.to_vec();
+    shuffled.shuffle(rng);
+
+    shuffled
+        .into_iter()
+        .enumerate()
+        .map(|(i, (entity, start))| {
+            let side = if i % 2 == 0 { RIGHT } else { LEFT };
+            Restack {
+                entity,
+                start,
+                before: timing.stagger * i as u32,
+                path: vec![
+                    start.translation + side,
+                    deck_translation + side + height(i),
+                ],
+                after: Duration::ZERO,
+                rotation: None,
+            }
+        })
+        .collect()
+}
+
+fn riffle(
+    cards: &[(Entity, Transform)],
+    deck_translation: Vec3,
+    timing: &AnimationTiming,
+    rng: &mut impl Rng,
+) -> Vec<Restack> {
+    let n = cards.len();
+    let split = rng.gen_range(n / 2 - n / 8..=n / 2 + n / 8);
+    let (bottom, top) = cards.split_at(split);
+
+    // both halves move aside, then drop one card at a time; the next card comes from either
+    // half with a probability proportional to the cards left in it
+    let (mut b, mut t) = (0, 0);
+    let mut plan = Vec::with_capacity(n);
+    while plan.len() < n {
+        let (left_b, left_t) = (bottom.len() - b, top.len() - t);
+        let ((entity, start), side, j) = if rng.gen_range(0..left_b + left_t) < left_b {
+            b += 1;
+            (bottom[b - 1], RIGHT, b - 1)
+        } else {
+            t += 1;
+            (top[t - 1], LEFT, t - 1)
+        };
+
+        let i = plan.len();
+        plan.push(Restack {
+            entity,
+            start,
+            before: Duration::ZERO,
+            path: vec![deck_translation + side + height(j)],
+            after: timing.stagger * i as u32,
+            rotation: None,
+        });
+    }
+
+    plan
+}
+
+fn overhand(
+    cards: &[(Entity, Transform)],
+    deck_translation: Vec3,
+    timing: &AnimationTiming,
+    rng: &mut impl Rng,
+) -> Vec<Restack> {
+    let n = cards.len();
+    let max_packet = (n / 5).max(1);
+
+    // packets peeled off the top, top card first
+    let top_first: Vec<(Entity, Transform)> = cards.iter().rev().copied().collect();
+    let mut packets = vec![];
+    let mut remaining = &top_first[..];
+    while !remaining.is_empty() {
+        let size = rng.gen_range(1..=max_packet).min(remaining.len());
+        let (packet, rest) = remaining.split_at(size);
+        packets.push(packet);
+        remaining = rest;
+    }
+
+    // the first packet ends up at the bottom of the new pile, every packet keeps its own order
+    let mut plan = Vec::with_capacity(n);
+    for (k, packet) in packets.iter().enumerate() {
+        for (entity, start) in packet.iter().rev() {
+            let i = plan.len();
+            plan.push(Restack {
+                entity: *entity,
+                start: *start,
+                before: timing.stagger * k as u32,
+                path: vec![deck_translation + LEFT + height(i)],
+                after: timing.stagger * (packets.len() - 1 - k) as u32,
+                rotation: None,
+            });
+        }
+    }
+
+    plan
+}
+
+fn cut(
+    cards: &[(Entity, Transform)],
+    deck_translation: Vec3,
+    timing: &AnimationTiming,
+    rng: &mut impl Rng,
+) -> Vec<Restack> {
+    let n = cards.len();
+    let split = if n < 2 {
+        0
+    } else {
+        rng.gen_range((n / 4).max(1)..=(3 * n / 4).min(n - 1))
+    };
+    let (bottom, top) = cards.split_at(split);
+
+    // the top part moves aside and lands first, the bottom part is lifted over it
+    let top_part = top.iter().enumerate().map(|(j, (entity, start))| Restack {
+        entity: *entity,
+        start: *start,
+        before: Duration::ZERO,
+        path: vec![deck_translation + RIGHT + height(j)],
+        after: timing.duration,
+        rotation: None,
+    });
+    let bottom_part = bottom
+        .iter()
+        .enumerate()
+        .map(|(j, (entity, start))| Restack {
+            entity: *entity,
+            start: *start,
+            before: timing.duration,
+            path: vec![deck_translation + LEFT + height(top.len() + j)],
+            after: timing.duration,
+            rotation: None,
+        });
+
+    top_part.chain(bottom_part).collect()
+}
+
+fn wash(
+    cards: &[(Entity, Transform)],
+    deck_translation: Vec3,
+    timing: &AnimationTiming,
+    rng: &mut impl Rng,
+) -> Vec<Restack> {
+    let mut shuffled = cards.to_vec();
+    shuffled.shuffle(rng);
+
+    shuffled
+        .into_iter()
+        .enumerate()
+        .map(|(i, (entity, start))| {
+            let spread = Vec3::new(rng.gen_range(-2.6..2.6), 0.0, rng.gen_range(-2.6..2.6));
+            Restack {
+                entity,
+                start,
+                before: Duration::ZERO,
+                path: vec![deck_translation + spread + height(i)],
+                after: timing.stagger * i as u32,
+                rotation: None,
+            }
+        })
+        .collect()
+}
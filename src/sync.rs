@@ -0,0 +1,787 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashSet, VecDeque},
+    fmt::Debug,
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+};
+
+use crate::ai::handle_ai_players;
+use crate::attach::{AttachCard, AttachSide, DetachCard};
+use crate::events::{
+    handle_align_cards_in_hand, handle_deck_shuffle, handle_reveal_card, AlignCardsInHand,
+    ClearDeck, Deal, DealOrder, DeckRendered, DeckShuffle, DeckShuffled, DespawnAllCards,
+    DiscardCardToDeck, DrawToHand, DrawToTable, LaMesaError, PlaceCardOnTable, RenderDeck,
+    ReorderDeck, ReplaceDeck, RevealCard,
+};
+use crate::peek::{MovePeekedCard, PeekDeck, Peeking, ResolvePeek};
+use crate::replay::handle_replay_recording;
+use crate::shuffle::{ReorderOp, ShuffleStyle, TableRng};
+use crate::table::TableQuery;
+use crate::visibility::CardVisibility;
+use crate::{Card, CardId, CardIndex, CardMetadata, CardZone};
+
+/// Card data that can be sent to a peer whose player may not see the card.
+pub trait Redact {
+    /// What a player who may not see the card is told about it. It keeps the back of the card
+    /// but must not tell cards apart, through [`CardMetadata::key`] or the face image either.
+    fn redacted(&self) -> Self;
+}
+
+/// [`ReorderOp`] naming cards by [`CardId`]. Sorting needs the card data, which clients do
+/// not have, so it cannot be sent.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeckReorder {
+    Cut(usize),
+    Reverse,
+    ToTop(CardId),
+    Order(Vec<CardId>),
+}
+
+/// Zone operation in a form that can be sent over the network: decks are named by marker and
+/// cards by their [`CardId`], never by entity.
+///
+/// Write these instead of the plain messages to keep every peer's table in step. Commands
+/// are only applied once the host has ordered them, on the host as well.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TableCommand<T> {
+    /// Clients are sent [`Redact::redacted`] cards and told what each one is through
+    /// [`TableCommand::RevealCard`] once their player may see it.
+    RenderDeck {
+        deck: usize,
+        cards: Vec<T>,
+    },
+    /// Sent to clients redacted, like [`TableCommand::RenderDeck`].
+    ReplaceDeck {
+        deck: usize,
+        cards: Vec<T>,
+    },
+    ClearDeck {
+        deck: usize,
+        animate: bool,
+    },
+    DespawnAllCards {
+        animate: bool,
+    },
+    DrawToHand {
+        deck: usize,
+        num_cards: usize,
+        player: usize,
+    },
+    DrawToTable {
+        deck: usize,
+        play_area_markers: Vec<usize>,
+        player: usize,
+    },
+    Deal {
+        deck: usize,
+        players: Vec<usize>,
+        cards_each: usize,
+        order: DealOrder,
+    },
+    PlaceCardOnTable {
+        card: CardId,
+        marker: usize,
+        player: usize,
+    },
+    DiscardCardToDeck {
+        card: CardId,
+        deck: usize,
+    },
+    AlignCardsInHand {
+        player: usize,
+    },
+    /// The host picks the seed, so every peer moves the same cards when the shuffle starts.
+    /// It then deals the card data out again among the shuffled cards in an order only it
+    /// knows, which makes the seed worthless to clients.
+    DeckShuffle {
+        deck: usize,
+        style: ShuffleStyle,
+        seed: Option<u64>,
+    },
+    ReorderDeck {
+        deck: usize,
+        op: DeckReorder,
+    },
+    PeekDeck {
+        deck: usize,
+        count: usize,
+        player: usize,
+    },
+    MovePeekedCard {
+        deck: usize,
+        card: CardId,
+        position: usize,
+    },
+    ResolvePeek {
+        deck: usize,
+        bottom: Vec<CardId>,
+    },
+    AttachCard {
+        card: CardId,
+        host: CardId,
+        side: AttachSide,
+    },
+    DetachCard {
+        card: CardId,
+    },
+    /// Tell what a card is. The host sends these on its own to the client whose player may
+    /// see the card, and ignores them from clients. Written on the host, the new data is only
+    /// sent to the clients that may see the card.
+    RevealCard {
+        card: CardId,
+        data: T,
+    },
+}
+
+impl<T: Send + Sync + 'static> Message for TableCommand<T> {}
+
+impl<T: Redact + Clone> TableCommand<T> {
+    /// The command as sent to a client.
+    fn redacted(&self) -> Self {
+        match self {
+            TableCommand::RenderDeck { deck, cards } => TableCommand::RenderDeck {
+                deck: *deck,
+                cards: cards.iter().map(Redact::redacted).collect(),
+            },
+            TableCommand::ReplaceDeck { deck, cards } => TableCommand::ReplaceDeck {
+                deck: *deck,
+                cards: cards.iter().map(Redact::redacted).collect(),
+            },
+            command => command.clone(),
+        }
+    }
+}
+
+impl<T> TableCommand<T> {
+    /// Cards the command names.
+    fn card_ids(&self) -> Vec<CardId> {
+        match self {
+            TableCommand::PlaceCardOnTable { card, .. }
+            | TableCommand::DiscardCardToDeck { card, .. }
+            | TableCommand::MovePeekedCard { card, .. }
+            | TableCommand::DetachCard { card }
+            | TableCommand::RevealCard { card, .. } => vec![*card],
+            TableCommand::ReorderDeck {
+                op: DeckReorder::ToTop(card),
+                ..
+            } => vec![*card],
+            TableCommand::ReorderDeck {
+                op: DeckReorder::Order(cards),
+                ..
+            }
+            | TableCommand::ResolvePeek { bottom: cards, .. } => cards.clone(),
+            TableCommand::AttachCard { card, host, .. } => vec![*card, *host],
+            _ => vec![],
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SyncPacket<T> {
+    /// Client asking the host to perform a command.
+    Request(TableCommand<T>),
+    /// Command ordered by the host; a client applies commands in `seq` order.
+    Apply { seq: u64, command: TableCommand<T> },
+}
+
+/// Moves packets between the host and one client.
+pub trait Transport<T>: Send + Sync + 'static {
+    fn send(&mut self, packet: SyncPacket<T>);
+    fn receive(&mut self) -> Vec<SyncPacket<T>>;
+}
+
+/// In-process transport, one end per peer.
+pub struct LoopbackTransport<T> {
+    inbox: Arc<Mutex<VecDeque<SyncPacket<T>>>>,
+    outbox: Arc<Mutex<VecDeque<SyncPacket<T>>>>,
+}
+
+impl<T> Default for LoopbackTransport<T> {
+    fn default() -> Self {
+        Self {
+            inbox: Arc::default(),
+            outbox: Arc::default(),
+        }
+    }
+}
+
+impl<T> Clone for LoopbackTransport<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inbox: self.inbox.clone(),
+            outbox: self.outbox.clone(),
+        }
+    }
+}
+
+impl<T> LoopbackTransport<T> {
+    /// Two connected ends, e.g. one for the host and one for a client.
+    pub fn pair() -> (Self, Self) {
+        let a = Self::default();
+        let b = Self {
+            inbox: a.outbox.clone(),
+            outbox: a.inbox.clone(),
+        };
+        (a, b)
+    }
+}
+
+impl<T: Send + 'static> Transport<T> for LoopbackTransport<T> {
+    fn send(&mut self, packet: SyncPacket<T>) {
+        self.outbox.lock().unwrap().push_back(packet);
+    }
+
+    fn receive(&mut self) -> Vec<SyncPacket<T>> {
+        self.inbox.lock().unwrap().drain(..).collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyncRole {
+    /// Authoritative peer: orders commands, picks shuffle seeds and reveals cards.
+    Host,
+    Client,
+}
+
+/// Connection to one peer: a client on the host, the host on a client.
+struct Link<T> {
+    /// Player of the client, whose hidden cards it is shown.
+    player: Option<usize>,
+    transport: Box<dyn Transport<T>>,
+    next_seq: u64,
+    /// Cards the client has been told about since they were last shuffled.
+    known: HashSet<CardId>,
+}
+
+impl<T: 'static> Link<T> {
+    fn send(&mut self, command: TableCommand<T>) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.transport.send(SyncPacket::Apply { seq, command });
+    }
+}
+
+#[derive(Resource)]
+pub struct TableSync<T> {
+    pub role: SyncRole,
+    links: Vec<Link<T>>,
+    /// Commands received out of order, waiting for the ones before them.
+    received: BTreeMap<u64, TableCommand<T>>,
+    /// Commands in the order the host put them, waiting to be applied.
+    ordered: VecDeque<TableCommand<T>>,
+    /// Decks rendered by a command that have not spawned their cards yet.
+    rendering: usize,
+}
+
+impl<T> TableSync<T> {
+    /// Host without clients, see [`TableSync::with_client`].
+    pub fn host() -> Self {
+        Self {
+            role: SyncRole::Host,
+            links: vec![],
+            received: BTreeMap::new(),
+            ordered: VecDeque::new(),
+            rendering: 0,
+        }
+    }
+
+    pub fn client(transport: impl Transport<T>) -> Self {
+        Self {
+            role: SyncRole::Client,
+            links: vec![Link {
+                player: None,
+                transport: Box::new(transport),
+                next_seq: 0,
+                known: HashSet::new(),
+            }],
+            received: BTreeMap::new(),
+            ordered: VecDeque::new(),
+            rendering: 0,
+        }
+    }
+
+    /// Host a client playing `player`.
+    pub fn with_client(mut self, player: usize, transport: impl Transport<T>) -> Self {
+        self.add_client(player, transport);
+        self
+    }
+
+    /// Host a client playing `player`. It is only sent the commands ordered from now on, so
+    /// clients have to join before the decks are rendered.
+    pub fn add_client(&mut self, player: usize, transport: impl Transport<T>) {
+        self.links.push(Link {
+            player: Some(player),
+            transport: Box::new(transport),
+            next_seq: 0,
+            known: HashSet::new(),
+        });
+    }
+}
+
+#[derive(SystemParam)]
+pub struct DeckWriters<'w, T>
+where
+    T: Send + Clone + Sync + Debug + CardMetadata + 'static,
+{
+    render: MessageWriter<'w, RenderDeck<T>>,
+    replace: MessageWriter<'w, ReplaceDeck<T>>,
+    clear: MessageWriter<'w, ClearDeck>,
+    despawn_all: MessageWriter<'w, DespawnAllCards>,
+    shuffle: MessageWriter<'w, DeckShuffle>,
+    reorder: MessageWriter<'w, ReorderDeck<T>>,
+    peek: MessageWriter<'w, PeekDeck>,
+    move_peeked: MessageWriter<'w, MovePeekedCard>,
+    resolve_peek: MessageWriter<'w, ResolvePeek>,
+}
+
+#[derive(SystemParam)]
+pub struct MoveWriters<'w> {
+    draw_to_hand: MessageWriter<'w, DrawToHand>,
+    draw_to_table: MessageWriter<'w, DrawToTable>,
+    deal: MessageWriter<'w, Deal>,
+    place: MessageWriter<'w, PlaceCardOnTable>,
+    discard: MessageWriter<'w, DiscardCardToDeck>,
+    align: MessageWriter<'w, AlignCardsInHand>,
+    attach: MessageWriter<'w, AttachCard>,
+    detach: MessageWriter<'w, DetachCard>,
+}
+
+#[derive(SystemParam)]
+pub struct TableWriters<'w, T>
+where
+    T: Send + Clone + Sync + Debug + CardMetadata + 'static,
+{
+    decks: DeckWriters<'w, T>,
+    moves: MoveWriters<'w>,
+    reveal: MessageWriter<'w, RevealCard<T>>,
+    pub(crate) error: MessageWriter<'w, LaMesaError>,
+}
+
+pub fn handle_table_sync<T>(
+    mut er_command: MessageReader<TableCommand<T>>,
+    mut er_rendered: MessageReader<DeckRendered>,
+    mut sync: ResMut<TableSync<T>>,
+    mut rng: ResMut<TableRng>,
+    table: TableQuery<T>,
+    mut writers: TableWriters<T>,
+) where
+    T: Send + Clone + Sync + Debug + CardMetadata + Redact + 'static,
+{
+    let sync = &mut *sync;
+    match sync.role {
+        SyncRole::Host => {
+            let mut commands: Vec<TableCommand<T>> = er_command.read().cloned().collect();
+            for link in sync.links.iter_mut() {
+                commands.extend(link.transport.receive().into_iter().filter_map(|packet| {
+                    match packet {
+                        // only the host knows what the cards are
+                        SyncPacket::Request(TableCommand::RevealCard { .. }) => None,
+                        SyncPacket::Request(command) => Some(command),
+                        SyncPacket::Apply { .. } => None,
+                    }
+                }));
+            }
+
+            for mut command in commands {
+                if let TableCommand::DeckShuffle { seed, .. } = &mut command {
+                    seed.get_or_insert_with(|| rng.rng().gen());
+                }
+                // reveals reach clients through `handle_reveals`, once they may see the card
+                if !matches!(command, TableCommand::RevealCard { .. }) {
+                    for link in sync.links.iter_mut() {
+                        link.send(command.redacted());
+                    }
+                }
+                sync.ordered.push_back(command);
+            }
+        }
+        SyncRole::Client => {
+            let link = &mut sync.links[0];
+            for command in er_command.read() {
+                link.transport.send(SyncPacket::Request(command.clone()));
+            }
+            for packet in link.transport.receive() {
+                if let SyncPacket::Apply { seq, command } = packet {
+                    sync.received.insert(seq, command);
+                }
+            }
+            while let Some(command) = sync.received.remove(&link.next_seq) {
+                link.next_seq += 1;
+                sync.ordered.push_back(command);
+            }
+        }
+    }
+
+    sync.rendering = sync.rendering.saturating_sub(er_rendered.read().count());
+    while let Some(command) = sync.ordered.front() {
+        // commands may name the cards of a deck being rendered, which get their ids once spawned
+        if sync.rendering > 0
+            && command
+                .card_ids()
+                .into_iter()
+                .any(|id| table.index().entity(id).is_none())
+        {
+            break;
+        }
+        let command = sync.ordered.pop_front().unwrap();
+        let rendered = match &command {
+            TableCommand::RenderDeck { deck, .. } | TableCommand::ReplaceDeck { deck, .. } => {
+                table.deck_area(*deck).is_some()
+            }
+            _ => false,
+        };
+
+        match (&command, sync.role) {
+            // the new data is sent to the clients that may see the card once it is applied
+            (TableCommand::RevealCard { card, .. }, SyncRole::Host) => {
+                for link in sync.links.iter_mut() {
+                    link.known.remove(card);
+                }
+            }
+            // the host deals the data out again when the shuffle starts, so whatever was
+            // known about the cards of the deck no longer holds
+            (TableCommand::DeckShuffle { deck, .. }, SyncRole::Client) => {
+                let in_deck = Some(CardZone::Deck { marker: *deck });
+                for (card_entity, data) in table
+                    .index()
+                    .iter()
+                    .filter(|(_, entry)| entry.zone == in_deck)
+                    .filter_map(|(_, entry)| Some((entry.entity, table.data(entry.entity)?)))
+                {
+                    writers.reveal.write(RevealCard {
+                        card_entity,
+                        data: data.redacted(),
+                    });
+                }
+            }
+            _ => {}
+        }
+        apply(command, &table, &mut writers);
+
+        // operations are queued in the order the plugin handles them, so the commands after
+        // a render wait for the next frame to be queued behind it
+        if rendered {
+            sync.rendering += 1;
+            break;
+        }
+    }
+}
+
+/// Deal the data of the cards of every deck shuffled on the host out again among them, so
+/// the seed sent with the shuffle does not tell clients where the cards went.
+pub fn handle_hidden_shuffles<T>(
+    mut er_shuffled: MessageReader<DeckShuffled>,
+    mut sync: ResMut<TableSync<T>>,
+    mut rng: ResMut<TableRng>,
+    q_cards: Query<&Card<T>>,
+    card_index: Res<CardIndex<T>>,
+    mut ew_reveal: MessageWriter<RevealCard<T>>,
+) where
+    T: Send + Clone + Sync + Debug + CardMetadata + 'static,
+{
+    if sync.role != SyncRole::Host {
+        er_shuffled.clear();
+        return;
+    }
+
+    for shuffled in er_shuffled.read() {
+        let (entities, mut data): (Vec<Entity>, Vec<T>) = shuffled
+            .cards
+            .iter()
+            .filter_map(|entity| {
+                q_cards
+                    .get(*entity)
+                    .ok()
+                    .map(|card| (*entity, card.data.clone()))
+            })
+            .unzip();
+        data.shuffle(rng.rng());
+
+        for (card_entity, data) in entities.iter().zip(data) {
+            ew_reveal.write(RevealCard {
+                card_entity: *card_entity,
+                data,
+            });
+        }
+        for id in entities
+            .iter()
+            .filter_map(|entity| card_index.id_of(*entity))
+        {
+            for link in sync.links.iter_mut() {
+                link.known.remove(&id);
+            }
+        }
+    }
+}
+
+/// Send every client the cards its player may see and has not been told about yet.
+pub fn handle_reveals<T>(
+    mut sync: ResMut<TableSync<T>>,
+    q_cards: Query<(&Card<T>, Option<&CardVisibility>)>,
+    q_peeking: Query<&Peeking>,
+    card_index: Res<CardIndex<T>>,
+) where
+    T: Send + Clone + Sync + Debug + CardMetadata + 'static,
+{
+    if sync.role != SyncRole::Host {
+        return;
+    }
+
+    for link in sync.links.iter_mut() {
+        let Some(player) = link.player else {
+            continue;
+        };
+        for (id, entry) in card_index.iter() {
+            if link.known.contains(id) {
+                continue;
+            }
+            let Ok((card, visibility)) = q_cards.get(entry.entity) else {
+                continue;
+            };
+            let visible = visibility.unwrap_or(&CardVisibility::ByZone).shows(
+                player,
+                entry.entity,
+                entry.zone,
+                &q_peeking,
+            );
+            if visible == Some(true) {
+                link.known.insert(*id);
+                link.send(TableCommand::RevealCard {
+                    card: *id,
+                    data: card.data.clone(),
+                });
+            }
+        }
+    }
+}
+
+/// Turn a command into the plain message handled by the plugin.
+pub(crate) fn apply<T>(
+    command: TableCommand<T>,
+    table: &TableQuery<T>,
+    writers: &mut TableWriters<T>,
+) where
+    T: Send + Clone + Sync + Debug + CardMetadata + 'static,
+{
+    let deck_entity = |marker: usize| {
+        table
+            .deck_area(marker)
+            .ok_or(LaMesaError::UnknownDeckMarker { marker })
+    };
+    let card_entity = |card_id: CardId| {
+        table
+            .index()
+            .entity(card_id)
+            .ok_or(LaMesaError::UnknownCardId { card_id })
+    };
+    let card_entities = |card_ids: Vec<CardId>| {
+        card_ids
+            .into_iter()
+            .map(card_entity)
+            .collect::<Result<Vec<Entity>, LaMesaError>>()
+    };
+    let decks = &mut writers.decks;
+    let moves = &mut writers.moves;
+
+    let result = match command {
+        TableCommand::RenderDeck { deck, cards } => deck_entity(deck).map(|deck_entity| {
+            decks.render.write(RenderDeck {
+                deck_entity,
+                deck: cards,
+            });
+        }),
+        TableCommand::ReplaceDeck { deck, cards } => deck_entity(deck).map(|deck_entity| {
+            decks.replace.write(ReplaceDeck {
+                deck_entity,
+                deck: cards,
+            });
+        }),
+        TableCommand::ClearDeck { deck, animate } => deck_entity(deck).map(|deck_entity| {
+            decks.clear.write(ClearDeck {
+                deck_entity,
+                animate,
+            });
+        }),
+        TableCommand::DespawnAllCards { animate } => {
+            decks.despawn_all.write(DespawnAllCards { animate });
+            Ok(())
+        }
+        TableCommand::DrawToHand {
+            deck,
+            num_cards,
+            player,
+        } => deck_entity(deck).map(|deck_entity| {
+            moves.draw_to_hand.write(DrawToHand {
+                deck_entity,
+                num_cards,
+                player,
+                timing: None,
+            });
+        }),
+        TableCommand::DrawToTable {
+            deck,
+            play_area_markers,
+            player,
+        } => deck_entity(deck).map(|deck_entity| {
+            moves.draw_to_table.write(DrawToTable {
+                deck_entity,
+                play_area_markers,
+                player,
+                timing: None,
+            });
+        }),
+        TableCommand::Deal {
+            deck,
+            players,
+            cards_each,
+            order,
+        } => deck_entity(deck).map(|deck_entity| {
+            moves.deal.write(Deal {
+                deck_entity,
+                players,
+                cards_each,
+                order,
+                timing: None,
+            });
+        }),
+        TableCommand::PlaceCardOnTable {
+            card,
+            marker,
+            player,
+        } => card_entity(card).map(|card_entity| {
+            moves.place.write(PlaceCardOnTable {
+                card_entity,
+                marker,
+                player,
+                timing: None,
+            });
+        }),
+        TableCommand::DiscardCardToDeck { card, deck } => {
+            card_entity(card).and_then(|card_entity| {
+                let deck_entity = deck_entity(deck)?;
+                moves.discard.write(DiscardCardToDeck {
+                    card_entity,
+                    deck_entity,
+                    timing: None,
+                });
+                Ok(())
+            })
+        }
+        TableCommand::AlignCardsInHand { player } => {
+            moves.align.write(AlignCardsInHand {
+                player,
+                timing: None,
+            });
+            Ok(())
+        }
+        TableCommand::DeckShuffle { deck, style, seed } => deck_entity(deck).map(|deck_entity| {
+            decks.shuffle.write(DeckShuffle {
+                deck_entity,
+                style,
+                seed,
+                timing: None,
+            });
+        }),
+        TableCommand::ReorderDeck { deck, op } => deck_entity(deck).and_then(|deck_entity| {
+            let op = match op {
+                DeckReorder::Cut(count) => ReorderOp::Cut(count),
+                DeckReorder::Reverse => ReorderOp::Reverse,
+                DeckReorder::ToTop(card) => ReorderOp::ToTop(card_entity(card)?),
+                DeckReorder::Order(cards) => ReorderOp::Order(card_entities(cards)?),
+            };
+            decks.reorder.write(ReorderDeck {
+                deck_entity,
+                op,
+                timing: None,
+            });
+            Ok(())
+        }),
+        TableCommand::PeekDeck {
+            deck,
+            count,
+            player,
+        } => deck_entity(deck).map(|deck_entity| {
+            decks.peek.write(PeekDeck {
+                deck_entity,
+                count,
+                player,
+                timing: None,
+            });
+        }),
+        TableCommand::MovePeekedCard {
+            deck,
+            card,
+            position,
+        } => deck_entity(deck).and_then(|deck_entity| {
+            decks.move_peeked.write(MovePeekedCard {
+                deck_entity,
+                card_entity: card_entity(card)?,
+                position,
+            });
+            Ok(())
+        }),
+        TableCommand::ResolvePeek { deck, bottom } => deck_entity(deck).and_then(|deck_entity| {
+            decks.resolve_peek.write(ResolvePeek {
+                deck_entity,
+                bottom: card_entities(bottom)?,
+            });
+            Ok(())
+        }),
+        TableCommand::AttachCard { card, host, side } => card_entity(card).and_then(|attached| {
+            moves.attach.write(AttachCard {
+                card_entity: attached,
+                host_entity: card_entity(host)?,
+                side,
+                timing: None,
+            });
+            Ok(())
+        }),
+        TableCommand::DetachCard { card } => card_entity(card).map(|card_entity| {
+            moves.detach.write(DetachCard { card_entity });
+        }),
+        TableCommand::RevealCard { card, data } => card_entity(card).map(|card_entity| {
+            writers.reveal.write(RevealCard { card_entity, data });
+        }),
+    };
+
+    if let Err(error) = result {
+        writers.error.write(error);
+    }
+}
+
+/// Replicates [`TableCommand`]s through the [`TableSync`] resource, which the game inserts once
+/// it knows whether it hosts or joins.
+pub struct SyncPlugin<T: Send + Clone + Sync + Debug + CardMetadata + 'static>(PhantomData<T>);
+
+impl<T: Send + Clone + Sync + Debug + CardMetadata + 'static> Default for SyncPlugin<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T> Plugin for SyncPlugin<T>
+where
+    T: Send + Clone + Sync + Debug + CardMetadata + Redact + 'static,
+{
+    fn build(&self, app: &mut App) {
+        app.add_message::<TableCommand<T>>().add_systems(
+            Update,
+            (
+                handle_table_sync::<T>
+                    .after(handle_ai_players::<T>)
+                    .before(handle_align_cards_in_hand::<T>)
+                    .before(handle_replay_recording::<T>),
+                // cards drawn right after a shuffle must be revealed with their new data
+                handle_hidden_shuffles::<T>
+                    .after(handle_deck_shuffle::<T>)
+                    .before(handle_reveal_card::<T>),
+                handle_reveals::<T>.after(handle_reveal_card::<T>),
+            )
+                .run_if(resource_exists::<TableSync<T>>),
+        );
+    }
+}
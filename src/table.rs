@@ -0,0 +1,146 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use std::cmp::Ordering;
+
+use crate::peek::Peeking;
+use crate::{Card, CardId, CardIndex, CardZone, DeckArea, HandArea, PlayArea};
+
+/// Read-only view of the table for game logic.
+///
+/// Zones come from [`CardIndex`], so cards that are still animating towards a zone are
+/// already reported as part of it. Ordering uses the card's resting transform when known.
+#[derive(SystemParam)]
+pub struct TableQuery<'w, 's, T>
+where
+    T: Send + Sync + 'static,
+{
+    card_index: Res<'w, CardIndex<T>>,
+    cards: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static Card<T>,
+            &'static Transform,
+            &'static CardId,
+        ),
+    >,
+    deck_areas: Query<'w, 's, (Entity, &'static DeckArea)>,
+    hand_areas: Query<'w, 's, (Entity, &'static HandArea, &'static Transform)>,
+    play_areas: Query<'w, 's, (Entity, &'static PlayArea)>,
+    peeking: Query<'w, 's, &'static Peeking>,
+}
+
+impl<'w, 's, T> TableQuery<'w, 's, T>
+where
+    T: Send + Sync + 'static,
+{
+    /// Cards in a player's hand, left to right as seen from the player's seat.
+    pub fn hand(&self, player: usize) -> Vec<(Entity, &T)> {
+        let mut cards = self.cards_in(CardZone::Hand { player });
+        let hand_transform = self
+            .hand_areas
+            .iter()
+            .find(|(_, hand_area, _)| hand_area.player == player)
+            .map_or(Transform::IDENTITY, |(_, _, transform)| *transform);
+        let along_hand = |card: &Card<T>, transform: &Transform| {
+            (hand_transform.rotation.inverse()
+                * (resting(card, transform) - hand_transform.translation))
+                .x
+        };
+        cards.sort_by(|a, b| cmp_f32(along_hand(a.1, a.2), along_hand(b.1, b.2)));
+        cards
+            .into_iter()
+            .map(|(entity, card, _)| (entity, &card.data))
+            .collect()
+    }
+
+    /// Cards of a deck, top card first. Cards lifted by a [`PeekDeck`](crate::peek::PeekDeck)
+    /// are left out until the peek is resolved.
+    pub fn deck(&self, marker: usize) -> Vec<(Entity, &T)> {
+        let mut cards = self.cards_in(CardZone::Deck { marker });
+        cards.retain(|(entity, _, _)| {
+            !self
+                .peeking
+                .iter()
+                .any(|peeking| peeking.cards.contains(entity))
+        });
+        cards.sort_by(|a, b| cmp_f32(resting(b.1, b.2).y, resting(a.1, a.2).y));
+        cards
+            .into_iter()
+            .map(|(entity, card, _)| (entity, &card.data))
+            .collect()
+    }
+
+    /// Top card of the deck placed on `deck_entity`.
+    pub fn top_of(&self, deck_entity: Entity) -> Option<(Entity, &T)> {
+        let (_, deck_area) = self.deck_areas.get(deck_entity).ok()?;
+        self.deck(deck_area.marker).into_iter().next()
+    }
+
+    /// Cards on a play area, bottom card first.
+    pub fn play_area(&self, marker: usize, player: usize) -> Vec<(Entity, &T)> {
+        let mut cards = self.cards_in(CardZone::Table { marker, player });
+        cards.sort_by(|a, b| cmp_f32(resting(a.1, a.2).y, resting(b.1, b.2).y));
+        cards
+            .into_iter()
+            .map(|(entity, card, _)| (entity, &card.data))
+            .collect()
+    }
+
+    pub fn zone_of(&self, card_entity: Entity) -> Option<CardZone> {
+        self.card_index.zone_of(card_entity)
+    }
+
+    pub fn data(&self, card_entity: Entity) -> Option<&T> {
+        self.cards
+            .get(card_entity)
+            .ok()
+            .map(|(_, card, _, _)| &card.data)
+    }
+
+    pub fn card_id(&self, card_entity: Entity) -> Option<CardId> {
+        self.card_index.id_of(card_entity)
+    }
+
+    pub fn index(&self) -> &CardIndex<T> {
+        &self.card_index
+    }
+
+    pub fn deck_area(&self, marker: usize) -> Option<Entity> {
+        self.deck_areas
+            .iter()
+            .find(|(_, deck_area)| deck_area.marker == marker)
+            .map(|(entity, _)| entity)
+    }
+
+    pub fn hand_area(&self, player: usize) -> Option<Entity> {
+        self.hand_areas
+            .iter()
+            .find(|(_, hand_area, _)| hand_area.player == player)
+            .map(|(entity, _, _)| entity)
+    }
+
+    pub fn play_area_entity(&self, marker: usize, player: usize) -> Option<Entity> {
+        self.play_areas
+            .iter()
+            .find(|(_, play_area)| play_area.marker == marker && play_area.player == player)
+            .map(|(entity, _)| entity)
+    }
+
+    fn cards_in(&self, zone: CardZone) -> Vec<(Entity, &Card<T>, &Transform)> {
+        self.cards
+            .iter()
+            .filter(|(entity, _, _, _)| self.card_index.zone_of(*entity) == Some(zone))
+            .map(|(entity, card, transform, _)| (entity, card, transform))
+            .collect()
+    }
+}
+
+fn resting<T>(card: &Card<T>, transform: &Transform) -> Vec3 {
+    card.transform.unwrap_or(*transform).translation
+}
+
+fn cmp_f32(a: f32, b: f32) -> Ordering {
+    a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+}
@@ -0,0 +1,286 @@
+//! Windowless table for testing game logic built on the plugin.
+//!
+//! ```ignore
+//! let mut table = TestTable::new(2).with_deck(1, test_deck(10));
+//! table.send(DrawToHand {
+//!     deck_entity: table.deck(1),
+//!     num_cards: 2,
+//!     player: 1,
+//!     timing: None,
+//! });
+//! table.settle();
+//! table.assert_hand(1, ["card-10", "card-9"]);
+//! ```
+
+use bevy::app::Plugins;
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::events::{LaMesaError, RenderDeck};
+use crate::queue::OperationQueue;
+use crate::sync::Redact;
+use crate::table::TableQuery;
+use crate::{
+    Card, CardId, CardMetadata, DeckArea, HandArea, LaMesaPlugin, LaMesaPluginSettings, PlayArea,
+};
+
+/// Most frames [`TestTable::settle`] runs before giving up.
+const MAX_FRAMES: usize = 1000;
+
+/// Card used by [`TestTable`], looked up by its `name`; redacted cards have no name.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TestCard {
+    pub name: String,
+    pub value: i64,
+}
+
+impl CardMetadata for TestCard {
+    type Output = TestCard;
+
+    fn front_image_filename(&self) -> String {
+        String::new()
+    }
+
+    fn back_image_filename(&self) -> String {
+        String::new()
+    }
+
+    fn key(&self) -> Option<String> {
+        (!self.name.is_empty()).then(|| self.name.clone())
+    }
+}
+
+impl Redact for TestCard {
+    fn redacted(&self) -> Self {
+        TestCard {
+            name: String::new(),
+            value: 0,
+        }
+    }
+}
+
+/// Cards `card-1` to `card-n`, worth their number, `card-1` at the bottom.
+pub fn test_deck(n: usize) -> Vec<TestCard> {
+    (1..=n)
+        .map(|i| TestCard {
+            name: format!("card-{i}"),
+            value: i as i64,
+        })
+        .collect()
+}
+
+/// Headless app running [`LaMesaPlugin`] with a hand area for every player.
+pub struct TestTable {
+    pub app: App,
+    decks: HashMap<usize, Entity>,
+    play_areas: HashMap<(usize, usize), Entity>,
+}
+
+impl TestTable {
+    /// Table for players `1..=num_players`, their hands spread along the x axis.
+    pub fn new(num_players: usize) -> Self {
+        Self::with_plugins(num_players, ())
+    }
+
+    /// [`TestTable::new`] running `plugins` as well, e.g. [`SyncPlugin`](crate::sync::SyncPlugin).
+    pub fn with_plugins<M>(num_players: usize, plugins: impl Plugins<M>) -> Self {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin))
+            .add_plugins(LaMesaPlugin::<TestCard>::headless())
+            .add_plugins(plugins)
+            .insert_resource(LaMesaPluginSettings { num_players });
+
+        for player in 1..=num_players {
+            app.world_mut().spawn((
+                Name::new(format!("HandArea - Player {player}")),
+                Transform::from_xyz(20.0 * player as f32, 0.0, 10.0),
+                HandArea { player },
+            ));
+        }
+
+        app.finish();
+        app.cleanup();
+
+        Self {
+            app,
+            decks: HashMap::new(),
+            play_areas: HashMap::new(),
+        }
+    }
+
+    /// Add a deck area with `marker` and render `cards` on it, the first card at the bottom.
+    pub fn with_deck(mut self, marker: usize, cards: Vec<TestCard>) -> Self {
+        let deck_entity = self
+            .app
+            .world_mut()
+            .spawn((
+                Name::new(format!("DeckArea {marker}")),
+                Transform::from_xyz(-10.0 * marker as f32, 0.0, 0.0),
+                DeckArea { marker },
+            ))
+            .id();
+        self.decks.insert(marker, deck_entity);
+        self.send(RenderDeck {
+            deck_entity,
+            deck: cards,
+        });
+        self.settle();
+        self
+    }
+
+    pub fn with_play_area(mut self, marker: usize, player: usize) -> Self {
+        let play_area_entity = self
+            .app
+            .world_mut()
+            .spawn((
+                Name::new(format!("PlayArea {marker} - Player {player}")),
+                Transform::from_xyz(20.0 * player as f32, 0.0, -5.0 * marker as f32),
+                PlayArea { marker, player },
+            ))
+            .id();
+        self.play_areas.insert((marker, player), play_area_entity);
+        self
+    }
+
+    /// Deck area added with [`with_deck`](Self::with_deck).
+    pub fn deck(&self, marker: usize) -> Entity {
+        self.decks[&marker]
+    }
+
+    /// Play area added with [`with_play_area`](Self::with_play_area).
+    pub fn play_area(&self, marker: usize, player: usize) -> Entity {
+        self.play_areas[&(marker, player)]
+    }
+
+    /// Entity of the card named `key`.
+    pub fn card(&mut self, key: &str) -> Entity {
+        let name = key.to_string();
+        self.query(move |table| table.index().entity_by_key(&name))
+            .unwrap_or_else(|| panic!("no card named {key}"))
+    }
+
+    pub fn send<M: Message>(&mut self, message: M) -> &mut Self {
+        self.app.world_mut().write_message(message);
+        self
+    }
+
+    pub fn update(&mut self) -> &mut Self {
+        self.app.update();
+        self
+    }
+
+    /// Run frames until every operation on the table is over.
+    pub fn settle(&mut self) -> &mut Self {
+        // messages are read in the first frame, the operations they start finish later
+        self.app.update();
+        for _ in 0..MAX_FRAMES {
+            self.app.update();
+            let now = self.app.world().resource::<Time>().elapsed();
+            if self.app.world().resource::<OperationQueue>().is_idle(now) {
+                return self;
+            }
+        }
+        panic!("table did not settle within {MAX_FRAMES} frames");
+    }
+
+    /// Errors reported during the last two frames.
+    pub fn errors(&self) -> Vec<LaMesaError> {
+        let messages = self.app.world().resource::<Messages<LaMesaError>>();
+        messages.get_cursor().read(messages).cloned().collect()
+    }
+
+    /// Run `f` on a read-only view of the table.
+    pub fn query<R: 'static>(
+        &mut self,
+        f: impl Fn(&TableQuery<TestCard>) -> R + Send + Sync + 'static,
+    ) -> R {
+        self.app
+            .world_mut()
+            .run_system_once(move |table: TableQuery<TestCard>| f(&table))
+            .expect("TableQuery is always available")
+    }
+
+    /// Names of the cards in a player's hand, left to right.
+    pub fn hand(&mut self, player: usize) -> Vec<String> {
+        self.query(move |table| names(table.hand(player).iter().map(|(_, card)| &card.name)))
+    }
+
+    /// Names of the cards in a deck, top card first.
+    pub fn deck_cards(&mut self, marker: usize) -> Vec<String> {
+        self.query(move |table| names(table.deck(marker).iter().map(|(_, card)| &card.name)))
+    }
+
+    /// Names of the cards on a play area, bottom card first.
+    pub fn play_area_cards(&mut self, marker: usize, player: usize) -> Vec<String> {
+        self.query(move |table| {
+            names(
+                table
+                    .play_area(marker, player)
+                    .iter()
+                    .map(|(_, card)| &card.name),
+            )
+        })
+    }
+
+    /// Every card with its transform, sorted by name.
+    pub fn card_transforms(&mut self) -> Vec<(String, Transform)> {
+        let world = self.app.world_mut();
+        let mut q_cards = world.query::<(&Card<TestCard>, &Transform)>();
+        let mut cards: Vec<(String, Transform)> = q_cards
+            .iter(world)
+            .map(|(card, transform)| (card.data.name.clone(), *transform))
+            .collect();
+        cards.sort_by(|a, b| a.0.cmp(&b.0));
+        cards
+    }
+
+    pub fn card_id(&mut self, key: &str) -> CardId {
+        let name = key.to_string();
+        self.query(move |table| table.index().by_key(&name))
+            .unwrap_or_else(|| panic!("no card named {key}"))
+    }
+
+    #[track_caller]
+    pub fn assert_hand<K: AsRef<str>>(
+        &mut self,
+        player: usize,
+        expected: impl IntoIterator<Item = K>,
+    ) {
+        let expected = names(expected);
+        assert_eq!(self.hand(player), expected, "hand of player {player}");
+    }
+
+    #[track_caller]
+    pub fn assert_deck<K: AsRef<str>>(
+        &mut self,
+        marker: usize,
+        expected: impl IntoIterator<Item = K>,
+    ) {
+        let expected = names(expected);
+        assert_eq!(self.deck_cards(marker), expected, "deck {marker}");
+    }
+
+    #[track_caller]
+    pub fn assert_play_area<K: AsRef<str>>(
+        &mut self,
+        marker: usize,
+        player: usize,
+        expected: impl IntoIterator<Item = K>,
+    ) {
+        let expected = names(expected);
+        assert_eq!(
+            self.play_area_cards(marker, player),
+            expected,
+            "play area {marker} of player {player}"
+        );
+    }
+}
+
+fn names<K: AsRef<str>>(keys: impl IntoIterator<Item = K>) -> Vec<String> {
+    keys.into_iter()
+        .map(|key| key.as_ref().to_string())
+        .collect()
+}
@@ -0,0 +1,250 @@
+use bevy::prelude::*;
+
+use crate::visibility::LocalPlayer;
+use crate::LaMesaPluginSettings;
+
+/// Phase of the current turn. Only the phases listed in [`TurnSettings::phases`] are entered.
+#[derive(States, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Phase {
+    #[default]
+    Draw,
+    Main,
+    Combat,
+    End,
+}
+
+/// Player whose turn it is.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CurrentPlayer(pub usize);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TurnDirection {
+    /// Players take turns in the order of [`TurnSettings::players`].
+    #[default]
+    Clockwise,
+    CounterClockwise,
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct TurnSettings {
+    /// Players in seating order. Left empty, players `1..=num_players` of
+    /// [`LaMesaPluginSettings`] take part.
+    pub players: Vec<usize>,
+    /// Phases of every turn, in order.
+    pub phases: Vec<Phase>,
+    pub direction: TurnDirection,
+}
+
+impl Default for TurnSettings {
+    fn default() -> Self {
+        Self {
+            players: vec![],
+            phases: vec![Phase::Draw, Phase::Main, Phase::Combat, Phase::End],
+            direction: TurnDirection::Clockwise,
+        }
+    }
+}
+
+/// End the turn of the current player, whatever phase it is in.
+#[derive(Message, Clone)]
+pub struct EndTurn;
+
+/// Move on to the next phase; ending the last phase ends the turn.
+#[derive(Message, Clone)]
+pub struct NextPhase;
+
+/// Flip [`TurnSettings::direction`].
+#[derive(Message, Clone)]
+pub struct ReverseTurnOrder;
+
+/// Skip the next turn of `player`. Sent twice, two turns are skipped.
+#[derive(Message, Clone)]
+pub struct SkipTurn {
+    pub player: usize,
+}
+
+#[derive(Message, Clone, Debug)]
+pub struct TurnStarted {
+    pub player: usize,
+    /// Number of the turn, starting at 1.
+    pub turn: u32,
+}
+
+#[derive(Message, Clone, Debug)]
+pub struct PhaseStarted {
+    pub player: usize,
+    pub phase: Phase,
+}
+
+/// Turn counter and skips still to be served.
+#[derive(Resource, Default)]
+pub struct TurnState {
+    turn: u32,
+    skips: Vec<usize>,
+}
+
+impl TurnState {
+    pub fn turn(&self) -> u32 {
+        self.turn
+    }
+}
+
+/// Run condition: it is `player`'s turn.
+pub fn is_players_turn(player: usize) -> impl FnMut(Option<Res<CurrentPlayer>>) -> bool + Clone {
+    move |current: Option<Res<CurrentPlayer>>| current.is_some_and(|current| current.0 == player)
+}
+
+/// Run condition: it is the turn of the player at this screen.
+pub fn is_local_players_turn(current: Option<Res<CurrentPlayer>>, local: Res<LocalPlayer>) -> bool {
+    current.is_some_and(|current| current.0 == local.0)
+}
+
+fn start_turns(
+    mut settings: ResMut<TurnSettings>,
+    plugin_settings: Option<Res<LaMesaPluginSettings>>,
+    mut current: ResMut<CurrentPlayer>,
+    mut state: ResMut<TurnState>,
+    mut next_phase: ResMut<NextState<Phase>>,
+    mut ew_turn: MessageWriter<TurnStarted>,
+    mut ew_phase: MessageWriter<PhaseStarted>,
+) {
+    if settings.players.is_empty() {
+        let num_players = plugin_settings.map_or(1, |s| s.num_players.max(1));
+        settings.players = (1..=num_players).collect();
+    }
+    let (Some(player), Some(phase)) = (settings.players.first(), settings.phases.first()) else {
+        return;
+    };
+
+    current.0 = *player;
+    state.turn = 1;
+    next_phase.set(*phase);
+    ew_turn.write(TurnStarted {
+        player: *player,
+        turn: 1,
+    });
+    ew_phase.write(PhaseStarted {
+        player: *player,
+        phase: *phase,
+    });
+}
+
+pub fn handle_turns(
+    mut er_end: MessageReader<EndTurn>,
+    mut er_next: MessageReader<NextPhase>,
+    mut er_reverse: MessageReader<ReverseTurnOrder>,
+    mut er_skip: MessageReader<SkipTurn>,
+    mut settings: ResMut<TurnSettings>,
+    mut current: ResMut<CurrentPlayer>,
+    mut state: ResMut<TurnState>,
+    phase: Res<State<Phase>>,
+    mut next_phase: ResMut<NextState<Phase>>,
+    mut ew_turn: MessageWriter<TurnStarted>,
+    mut ew_phase: MessageWriter<PhaseStarted>,
+) {
+    for _ in er_reverse.read() {
+        settings.direction = match settings.direction {
+            TurnDirection::Clockwise => TurnDirection::CounterClockwise,
+            TurnDirection::CounterClockwise => TurnDirection::Clockwise,
+        };
+    }
+    state.skips.extend(er_skip.read().map(|skip| skip.player));
+
+    if settings.players.is_empty() || settings.phases.is_empty() {
+        er_next.clear();
+        er_end.clear();
+        return;
+    }
+
+    // phase changes only show up in `State` next frame, so keep track of it here
+    let mut index = settings
+        .phases
+        .iter()
+        .position(|p| p == phase.get())
+        .unwrap_or(0);
+    let mut changed = false;
+    let mut end_turn = |index: &mut usize, current: &mut CurrentPlayer, state: &mut TurnState| {
+        current.0 = next_player(&settings, current.0, &mut state.skips);
+        state.turn += 1;
+        *index = 0;
+        ew_turn.write(TurnStarted {
+            player: current.0,
+            turn: state.turn,
+        });
+    };
+
+    for _ in er_next.read() {
+        if index + 1 < settings.phases.len() {
+            index += 1;
+        } else {
+            end_turn(&mut index, &mut current, &mut state);
+        }
+        changed = true;
+        ew_phase.write(PhaseStarted {
+            player: current.0,
+            phase: settings.phases[index],
+        });
+    }
+    for _ in er_end.read() {
+        end_turn(&mut index, &mut current, &mut state);
+        changed = true;
+        ew_phase.write(PhaseStarted {
+            player: current.0,
+            phase: settings.phases[index],
+        });
+    }
+
+    if changed {
+        next_phase.set(settings.phases[index]);
+    }
+}
+
+/// Player after `current` in turn order, passing over players with a skip to serve.
+fn next_player(settings: &TurnSettings, current: usize, skips: &mut Vec<usize>) -> usize {
+    let n = settings.players.len();
+    let mut i = settings
+        .players
+        .iter()
+        .position(|p| *p == current)
+        .unwrap_or(0);
+
+    // at most one time around the table, so skipping everybody still ends somewhere
+    for _ in 0..n {
+        i = match settings.direction {
+            TurnDirection::Clockwise => (i + 1) % n,
+            TurnDirection::CounterClockwise => (i + n - 1) % n,
+        };
+        let player = settings.players[i];
+        match skips.iter().position(|p| *p == player) {
+            Some(skip) => {
+                skips.remove(skip);
+            }
+            None => return player,
+        }
+    }
+
+    settings.players[i]
+}
+
+/// Whose turn it is and which phase it is in, driven by [`EndTurn`] and [`NextPhase`].
+#[derive(Default)]
+pub struct TurnPlugin {
+    pub settings: TurnSettings,
+}
+
+impl Plugin for TurnPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<Phase>()
+            .insert_resource(self.settings.clone())
+            .init_resource::<CurrentPlayer>()
+            .init_resource::<TurnState>()
+            .add_message::<EndTurn>()
+            .add_message::<NextPhase>()
+            .add_message::<PhaseStarted>()
+            .add_message::<ReverseTurnOrder>()
+            .add_message::<SkipTurn>()
+            .add_message::<TurnStarted>()
+            .add_systems(Startup, start_turns)
+            .add_systems(Update, handle_turns);
+    }
+}
@@ -0,0 +1,106 @@
+use bevy::prelude::*;
+use std::fmt::Debug;
+
+use crate::peek::Peeking;
+use crate::{Card, CardCover, CardFace, CardIndex, CardZone};
+
+/// Player whose point of view the local camera shows.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LocalPlayer(pub usize);
+
+impl Default for LocalPlayer {
+    fn default() -> Self {
+        LocalPlayer(1)
+    }
+}
+
+/// Which players may see the face of a card. Cards without this component follow
+/// [`CardVisibility::ByZone`].
+#[derive(Component, Clone, Debug, Default, PartialEq, Eq)]
+pub enum CardVisibility {
+    /// Face visible on the table, to the owner of a hand, and to the player peeking at a deck.
+    #[default]
+    ByZone,
+    Everyone,
+    Nobody,
+    Players(Vec<usize>),
+}
+
+impl CardVisibility {
+    /// Whether `player` may see the face of `entity`, a card in `zone`; `None` for a card that
+    /// follows its zone but is in none, such as a card being despawned.
+    pub(crate) fn shows(
+        &self,
+        player: usize,
+        entity: Entity,
+        zone: Option<CardZone>,
+        q_peeking: &Query<&Peeking>,
+    ) -> Option<bool> {
+        Some(match self {
+            CardVisibility::ByZone => match zone? {
+                CardZone::Hand { player: owner } => owner == player,
+                CardZone::Table { .. } => true,
+                CardZone::Deck { .. } => q_peeking
+                    .iter()
+                    .any(|peeking| peeking.player == player && peeking.cards.contains(&entity)),
+            },
+            CardVisibility::Everyone => true,
+            CardVisibility::Nobody => false,
+            CardVisibility::Players(players) => players.contains(&player),
+        })
+    }
+}
+
+/// Card whose face the [`LocalPlayer`] may not see; its face is covered by a second back.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct FaceHidden;
+
+pub fn handle_card_visibility<T>(
+    mut commands: Commands,
+    q_cards: Query<(Entity, Option<&CardVisibility>, Has<FaceHidden>, &Children), With<Card<T>>>,
+    mut q_faces: Query<&mut Visibility, (With<CardFace>, Without<CardCover>)>,
+    mut q_covers: Query<&mut Visibility, (With<CardCover>, Without<CardFace>)>,
+    q_peeking: Query<&Peeking>,
+    card_index: Res<CardIndex<T>>,
+    local_player: Res<LocalPlayer>,
+) where
+    T: Send + Sync + Debug + 'static,
+{
+    for (entity, visibility, hidden, children) in q_cards.iter() {
+        // cards being despawned keep what they showed
+        let Some(visible) = visibility.unwrap_or(&CardVisibility::ByZone).shows(
+            local_player.0,
+            entity,
+            card_index.zone_of(entity),
+            &q_peeking,
+        ) else {
+            continue;
+        };
+        if visible != hidden {
+            continue;
+        }
+
+        for child in children.iter() {
+            if let Ok(mut face) = q_faces.get_mut(child) {
+                *face = if visible {
+                    Visibility::Inherited
+                } else {
+                    Visibility::Hidden
+                };
+            }
+            if let Ok(mut cover) = q_covers.get_mut(child) {
+                *cover = if visible {
+                    Visibility::Hidden
+                } else {
+                    Visibility::Inherited
+                };
+            }
+        }
+
+        if visible {
+            commands.entity(entity).remove::<FaceHidden>();
+        } else {
+            commands.entity(entity).insert(FaceHidden);
+        }
+    }
+}
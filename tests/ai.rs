@@ -0,0 +1,163 @@
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_la_mesa::ai::{AiAction, AiPlayer, AiPlugin, AiSeats, AiView};
+use bevy_la_mesa::peek::{PeekDeck, ResolvePeek};
+use bevy_la_mesa::sync::{LoopbackTransport, SyncPlugin, TableCommand, TableSync};
+use bevy_la_mesa::testing::{test_deck, TestCard, TestTable};
+use bevy_la_mesa::turn::{CurrentPlayer, Phase, TurnPlugin};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Bot that writes down when it was asked to think and ends its turn.
+struct Quitter(Arc<Mutex<Vec<(usize, Phase)>>>);
+
+impl AiPlayer<TestCard> for Quitter {
+    fn think(&mut self, view: &AiView<TestCard>) -> Vec<AiAction<TestCard>> {
+        self.0.lock().unwrap().push((view.player, view.phase));
+        vec![AiAction::EndTurn]
+    }
+}
+
+/// Two players, the first one a bot waiting 500ms before it acts, 100ms a frame.
+fn table() -> (TestTable, Arc<Mutex<Vec<(usize, Phase)>>>) {
+    let mut table =
+        TestTable::with_plugins(2, (TurnPlugin::default(), AiPlugin::<TestCard>::default()))
+            .with_deck(1, test_deck(3));
+    let thoughts = Arc::new(Mutex::new(Vec::new()));
+    let mut seats = table.app.world_mut().resource_mut::<AiSeats<TestCard>>();
+    seats.add(1, Quitter(thoughts.clone()));
+    seats.think_delay = Duration::from_millis(500);
+    table
+        .app
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            100,
+        )));
+    (table, thoughts)
+}
+
+fn elapsed(table: &TestTable) -> Duration {
+    table.app.world().resource::<Time>().elapsed()
+}
+
+/// Run frames until the bot has thought `count` times in all, returning how long that took.
+fn think(
+    table: &mut TestTable,
+    thoughts: &Arc<Mutex<Vec<(usize, Phase)>>>,
+    count: usize,
+) -> Duration {
+    let start = elapsed(table);
+    for _ in 0..50 {
+        table.update();
+        if thoughts.lock().unwrap().len() == count {
+            return elapsed(table) - start;
+        }
+    }
+    panic!("the bot did not think");
+}
+
+#[test]
+fn the_bot_waits_its_delay_and_only_plays_its_own_seat() {
+    let (mut table, thoughts) = table();
+
+    let waited = think(&mut table, &thoughts, 1);
+    assert!(waited >= Duration::from_millis(500), "waited {waited:?}");
+    assert!(waited <= Duration::from_millis(800), "waited {waited:?}");
+    assert_eq!(*thoughts.lock().unwrap(), [(1, Phase::Draw)]);
+
+    for _ in 0..20 {
+        table.update();
+    }
+    assert_eq!(table.app.world().resource::<CurrentPlayer>().0, 2);
+    assert_eq!(thoughts.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn the_bot_waits_for_the_table_to_settle() {
+    let (mut table, thoughts) = table();
+    let deck_entity = table.deck(1);
+    table.send(PeekDeck {
+        deck_entity,
+        count: 2,
+        player: 2,
+        timing: None,
+    });
+
+    // the peek locks the deck until it is resolved
+    for _ in 0..20 {
+        table.update();
+    }
+    assert!(thoughts.lock().unwrap().is_empty());
+
+    table.send(ResolvePeek {
+        deck_entity,
+        bottom: vec![],
+    });
+    let waited = think(&mut table, &thoughts, 1);
+    assert!(waited >= Duration::from_millis(500), "waited {waited:?}");
+}
+
+/// Bot that draws a card and ends its turn.
+struct Drawer;
+
+impl AiPlayer<TestCard> for Drawer {
+    fn think(&mut self, view: &AiView<TestCard>) -> Vec<AiAction<TestCard>> {
+        vec![
+            AiAction::Table(TableCommand::DrawToHand {
+                deck: 1,
+                num_cards: 1,
+                player: view.player,
+            }),
+            AiAction::EndTurn,
+        ]
+    }
+}
+
+#[test]
+fn a_bot_on_a_host_moves_the_cards_of_its_clients() {
+    let (host_end, client_end) = LoopbackTransport::<TestCard>::pair();
+    let mut host = TestTable::with_plugins(
+        2,
+        (
+            SyncPlugin::<TestCard>::default(),
+            TurnPlugin::default(),
+            AiPlugin::<TestCard>::default(),
+        ),
+    )
+    .with_deck(1, vec![]);
+    let mut client =
+        TestTable::with_plugins(2, SyncPlugin::<TestCard>::default()).with_deck(1, vec![]);
+    host.app
+        .insert_resource(TableSync::host().with_client(2, host_end));
+    client.app.insert_resource(TableSync::client(client_end));
+    host.send(TableCommand::RenderDeck {
+        deck: 1,
+        cards: test_deck(3),
+    });
+    for _ in 0..10 {
+        host.update();
+        client.update();
+    }
+
+    let mut seats = host.app.world_mut().resource_mut::<AiSeats<TestCard>>();
+    seats.add(1, Drawer);
+    seats.think_delay = Duration::ZERO;
+    for _ in 0..20 {
+        host.update();
+        client.update();
+    }
+
+    let hand = |table: &mut TestTable| {
+        table.query(|table| {
+            table
+                .hand(1)
+                .into_iter()
+                .map(|(entity, _)| table.card_id(entity).unwrap())
+                .collect::<Vec<_>>()
+        })
+    };
+    assert_eq!(host.app.world().resource::<CurrentPlayer>().0, 2);
+    assert_eq!(host.hand(1), ["card-3"]);
+    assert_eq!(hand(&mut client), hand(&mut host));
+    assert!(host.errors().is_empty());
+    assert!(client.errors().is_empty());
+}
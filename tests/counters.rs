@@ -0,0 +1,55 @@
+use bevy::prelude::*;
+use bevy_la_mesa::counters::{handle_counter_badges, BadgeSettings, CounterBadge, Counters};
+
+#[test]
+fn counters_at_zero_are_removed() {
+    let mut counters = Counters::default();
+
+    assert_eq!(counters.add("damage", 3), 3);
+    assert_eq!(counters.add("damage", -1), 2);
+    counters.set("poison", 1);
+    assert_eq!(
+        counters.iter().collect::<Vec<_>>(),
+        [("damage", 2), ("poison", 1)]
+    );
+
+    assert_eq!(counters.add("damage", -2), 0);
+    counters.set("poison", 0);
+    assert_eq!(counters.get("damage"), 0);
+    assert!(counters.is_empty());
+}
+
+fn badges(app: &mut App) -> Vec<Entity> {
+    let world = app.world_mut();
+    let mut q_badges = world.query::<&CounterBadge>();
+    q_badges.iter(world).map(|badge| badge.card).collect()
+}
+
+#[test]
+fn badges_go_away_with_their_counters() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .init_resource::<BadgeSettings>()
+        .add_systems(Update, handle_counter_badges);
+
+    let mut counters = Counters::default();
+    counters.add("damage", 2);
+    let card = app
+        .world_mut()
+        .spawn((Transform::default(), GlobalTransform::default(), counters))
+        .id();
+    app.update();
+    assert_eq!(badges(&mut app), [card]);
+
+    // rebuilt, not duplicated, when the counters change
+    app.world_mut()
+        .get_mut::<Counters>(card)
+        .unwrap()
+        .add("damage", 1);
+    app.update();
+    assert_eq!(badges(&mut app), [card]);
+
+    app.world_mut().entity_mut(card).remove::<Counters>();
+    app.update();
+    assert!(badges(&mut app).is_empty());
+}
@@ -0,0 +1,151 @@
+//! Needs the `render` feature: these tests look at card materials.
+#![cfg(feature = "render")]
+
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_la_mesa::highlight::{CardOutline, Dimmed, Highlight, HighlightMode};
+use bevy_la_mesa::testing::{test_deck, TestTable};
+use bevy_la_mesa::CardFace;
+use std::time::Duration;
+
+/// Table with a single card that has a face material of its own.
+fn table() -> (TestTable, Entity) {
+    let mut table = TestTable::new(1).with_deck(1, test_deck(1));
+    table
+        .app
+        .init_resource::<Assets<Mesh>>()
+        .init_resource::<Assets<StandardMaterial>>();
+
+    let card_entity = table.card("card-1");
+    let world = table.app.world_mut();
+    let material = world
+        .resource_mut::<Assets<StandardMaterial>>()
+        .add(StandardMaterial::default());
+    world.spawn((CardFace, MeshMaterial3d(material), ChildOf(card_entity)));
+    (table, card_entity)
+}
+
+fn set(table: &mut TestTable, card_entity: Entity, bundle: impl Bundle) {
+    table.app.world_mut().entity_mut(card_entity).insert(bundle);
+    table.update();
+}
+
+fn unset<B: Bundle>(table: &mut TestTable, card_entity: Entity) {
+    table.app.world_mut().entity_mut(card_entity).remove::<B>();
+    table.update();
+}
+
+/// Base and emissive color of the face.
+fn face(table: &mut TestTable) -> (LinearRgba, LinearRgba) {
+    let world = table.app.world_mut();
+    let handle = world
+        .query_filtered::<&MeshMaterial3d<StandardMaterial>, With<CardFace>>()
+        .single(world)
+        .unwrap()
+        .0
+        .clone();
+    let material = world
+        .resource::<Assets<StandardMaterial>>()
+        .get(&handle)
+        .unwrap();
+    (material.base_color.to_linear(), material.emissive)
+}
+
+fn outlines(table: &mut TestTable) -> Vec<LinearRgba> {
+    let world = table.app.world_mut();
+    let handles: Vec<_> = world
+        .query_filtered::<&MeshMaterial3d<StandardMaterial>, With<CardOutline>>()
+        .iter(world)
+        .map(|material| material.0.clone())
+        .collect();
+    let materials = world.resource::<Assets<StandardMaterial>>();
+    handles
+        .iter()
+        .map(|handle| materials.get(handle).unwrap().base_color.to_linear())
+        .collect()
+}
+
+const RED: LinearRgba = LinearRgba::RED;
+
+#[test]
+fn a_glow_lights_the_face_until_it_is_removed() {
+    let (mut table, card_entity) = table();
+
+    set(&mut table, card_entity, Highlight::glow(RED.into()));
+    assert_eq!(face(&mut table), (LinearRgba::WHITE, RED * 2.0));
+
+    unset::<Highlight>(&mut table, card_entity);
+    assert_eq!(face(&mut table), (LinearRgba::WHITE, LinearRgba::BLACK));
+}
+
+#[test]
+fn dimming_darkens_the_tint() {
+    let (mut table, card_entity) = table();
+
+    set(
+        &mut table,
+        card_entity,
+        (Highlight::tint(RED.into()), Dimmed),
+    );
+    assert_eq!(
+        face(&mut table),
+        (LinearRgba::new(0.35, 0.0, 0.0, 1.0), LinearRgba::BLACK)
+    );
+
+    unset::<Dimmed>(&mut table, card_entity);
+    assert_eq!(face(&mut table), (RED, LinearRgba::BLACK));
+
+    unset::<Highlight>(&mut table, card_entity);
+    set(&mut table, card_entity, Dimmed);
+    assert_eq!(
+        face(&mut table),
+        (LinearRgba::rgb(0.35, 0.35, 0.35), LinearRgba::BLACK)
+    );
+}
+
+#[test]
+fn an_outline_is_replaced_and_removed_with_the_highlight() {
+    let (mut table, card_entity) = table();
+
+    set(&mut table, card_entity, Highlight::outline(RED.into()));
+    assert_eq!(outlines(&mut table), [RED]);
+    assert_eq!(face(&mut table), (LinearRgba::WHITE, LinearRgba::BLACK));
+
+    let blue = Highlight::outline(LinearRgba::BLUE.into());
+    set(&mut table, card_entity, blue);
+    assert_eq!(outlines(&mut table), [LinearRgba::BLUE]);
+
+    let wide = Highlight {
+        mode: HighlightMode::Outline { width: 0.3 },
+        ..blue
+    };
+    set(&mut table, card_entity, wide);
+    assert_eq!(outlines(&mut table), [LinearRgba::BLUE]);
+
+    unset::<Highlight>(&mut table, card_entity);
+    assert!(outlines(&mut table).is_empty());
+}
+
+#[test]
+fn a_pulsing_glow_changes_every_frame() {
+    let (mut table, card_entity) = table();
+    table
+        .app
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            100,
+        )));
+
+    set(
+        &mut table,
+        card_entity,
+        Highlight::glow(RED.into()).pulsing(1.0),
+    );
+    let first = face(&mut table).1;
+    table.update();
+    let second = face(&mut table).1;
+
+    assert_ne!(first, second);
+    for emissive in [first, second] {
+        assert!(emissive.red >= 0.8 && emissive.red <= 2.0, "{emissive:?}");
+    }
+}
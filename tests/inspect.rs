@@ -0,0 +1,168 @@
+//! Needs the `render` feature: these tests look at card materials.
+#![cfg(feature = "render")]
+
+use bevy::camera::NormalizedRenderTarget;
+use bevy::picking::backend::HitData;
+use bevy::picking::pointer::{Location, PointerButton, PointerId};
+use bevy::prelude::*;
+use bevy_la_mesa::events::{CardPress, DrawToHand};
+use bevy_la_mesa::inspect::{
+    CardInspected, DismissInspect, InspectDismissed, InspectedCopy, Inspection,
+};
+use bevy_la_mesa::testing::{test_deck, TestCard, TestTable};
+use bevy_la_mesa::{Card, CardFace};
+use std::time::Duration;
+
+/// Table with a camera looking down on it and a face material on every card, so cards can be
+/// inspected without rendering.
+fn table() -> TestTable {
+    let mut table = TestTable::new(1).with_deck(1, test_deck(3));
+    table.app.init_resource::<Assets<Mesh>>();
+
+    let world = table.app.world_mut();
+    world.spawn((
+        Camera3d::default(),
+        GlobalTransform::from(Transform::from_xyz(0.0, 30.0, 0.0).looking_at(Vec3::ZERO, Vec3::Z)),
+    ));
+    let mut q_cards = world.query_filtered::<Entity, With<Card<TestCard>>>();
+    for card in q_cards.iter(world).collect::<Vec<_>>() {
+        world.entity_mut(card).insert(Visibility::Inherited);
+        world.spawn((
+            CardFace,
+            MeshMaterial3d::<StandardMaterial>(Handle::default()),
+            ChildOf(card),
+        ));
+    }
+
+    let deck_entity = table.deck(1);
+    table
+        .send(DrawToHand {
+            deck_entity,
+            num_cards: 1,
+            player: 1,
+            timing: None,
+        })
+        .settle();
+    table
+}
+
+fn location() -> Location {
+    Location {
+        target: NormalizedRenderTarget::None {
+            width: 1,
+            height: 1,
+        },
+        position: Vec2::ZERO,
+    }
+}
+
+fn hit() -> HitData {
+    HitData::new(Entity::PLACEHOLDER, 0.0, None, None)
+}
+
+fn right_press(table: &mut TestTable, entity: Entity) {
+    let press = Press {
+        button: PointerButton::Secondary,
+        hit: hit(),
+    };
+    table
+        .app
+        .world_mut()
+        .trigger(Pointer::new(PointerId::Mouse, location(), press, entity));
+    table.update();
+}
+
+fn right_click(table: &mut TestTable, entity: Entity) {
+    let click = Click {
+        button: PointerButton::Secondary,
+        hit: hit(),
+        duration: Duration::from_millis(100),
+    };
+    table
+        .app
+        .world_mut()
+        .trigger(Pointer::new(PointerId::Mouse, location(), click, entity));
+}
+
+/// Cards named by the messages of type `M` sent during the last two frames.
+fn sent<M: Message>(table: &TestTable, card: impl Fn(&M) -> Entity) -> Vec<Entity> {
+    let messages = table.app.world().resource::<Messages<M>>();
+    messages.get_cursor().read(messages).map(card).collect()
+}
+
+fn copies(table: &mut TestTable) -> usize {
+    let world = table.app.world_mut();
+    world
+        .query_filtered::<(), With<InspectedCopy>>()
+        .iter(world)
+        .count()
+}
+
+#[test]
+fn inspecting_twice_toggles_the_copy() {
+    let mut table = table();
+    let card_entity = table.card("card-3");
+
+    right_press(&mut table, card_entity);
+    assert_eq!(
+        sent(&table, |m: &CardInspected| m.card_entity),
+        [card_entity]
+    );
+    assert_eq!(
+        table.app.world().resource::<Inspection>().card(),
+        Some(card_entity)
+    );
+    assert_eq!(
+        table.app.world().get::<Visibility>(card_entity),
+        Some(&Visibility::Hidden)
+    );
+    assert_eq!(copies(&mut table), 1);
+
+    right_press(&mut table, card_entity);
+    assert_eq!(
+        sent(&table, |m: &InspectDismissed| m.card_entity),
+        [card_entity]
+    );
+    assert_eq!(table.app.world().resource::<Inspection>().card(), None);
+    assert_eq!(
+        table.app.world().get::<Visibility>(card_entity),
+        Some(&Visibility::Inherited)
+    );
+    assert_eq!(copies(&mut table), 0);
+}
+
+#[test]
+fn dismiss_hides_the_copy() {
+    let mut table = table();
+    let card_entity = table.card("card-3");
+    right_press(&mut table, card_entity);
+
+    table.send(DismissInspect).update();
+
+    assert_eq!(table.app.world().resource::<Inspection>().card(), None);
+    assert_eq!(copies(&mut table), 0);
+}
+
+#[test]
+fn the_click_that_inspected_does_not_press_the_card() {
+    let mut table = table();
+    let card_entity = table.card("card-3");
+
+    right_press(&mut table, card_entity);
+    right_click(&mut table, card_entity);
+
+    assert!(sent(&table, |m: &CardPress| m.entity).is_empty());
+}
+
+#[test]
+fn face_down_cards_are_not_inspected_and_keep_their_click() {
+    let mut table = table();
+    let card_entity = table.card("card-2");
+
+    right_press(&mut table, card_entity);
+    assert!(sent(&table, |m: &CardInspected| m.card_entity).is_empty());
+    assert_eq!(copies(&mut table), 0);
+
+    right_click(&mut table, card_entity);
+    assert_eq!(sent(&table, |m: &CardPress| m.entity), [card_entity]);
+}
@@ -0,0 +1,163 @@
+//! Final card transforms after scripted operations, compared against the snapshots in
+//! `tests/snapshots`. Run with `UPDATE_SNAPSHOTS=1` to write the current layout instead.
+
+use bevy::prelude::*;
+use bevy_la_mesa::events::{AlignCardsInHand, DiscardCardToDeck, DrawToHand};
+use bevy_la_mesa::testing::{test_deck, TestTable};
+use bevy_la_mesa::{HandArea, DECK_WIDTH};
+use std::{env, fs, path::PathBuf};
+
+/// Largest difference between a snapshot value and the actual one.
+const TOLERANCE: f32 = 1e-3;
+
+fn draw(table: &mut TestTable, num_cards: usize, player: usize) {
+    let deck_entity = table.deck(1);
+    table
+        .send(DrawToHand {
+            deck_entity,
+            num_cards,
+            player,
+            timing: None,
+        })
+        .settle();
+}
+
+fn discard(table: &mut TestTable, key: &str) {
+    let card_entity = table.card(key);
+    let deck_entity = table.deck(1);
+    table
+        .send(DiscardCardToDeck {
+            card_entity,
+            deck_entity,
+            timing: None,
+        })
+        .settle();
+}
+
+fn render(cards: &[(String, Transform)]) -> String {
+    let mut out = String::from("# card  translation x y z  rotation x y z w\n");
+    for (name, transform) in cards {
+        let t = transform.translation;
+        let r = transform.rotation;
+        let values = [t.x, t.y, t.z, r.x, r.y, r.z, r.w]
+            .map(|v| format!("{:.3}", (v * 1000.0).round() / 1000.0 + 0.0));
+        out.push_str(&format!("{name} {}\n", values.join(" ")));
+    }
+    out
+}
+
+fn parse(snapshot: &str) -> Vec<(String, Vec<f32>)> {
+    snapshot
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let mut fields = line.split_whitespace();
+            let name = fields.next().unwrap_or_default().to_string();
+            let values = fields
+                .map(|v| v.parse().unwrap_or_else(|_| panic!("bad value {v:?}")))
+                .collect();
+            (name, values)
+        })
+        .collect()
+}
+
+#[track_caller]
+fn assert_snapshot(name: &str, table: &mut TestTable) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{name}.txt"));
+    let actual = render(&table.card_transforms());
+
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&path, &actual).expect("snapshot is writable");
+        return;
+    }
+
+    let expected =
+        fs::read_to_string(&path).unwrap_or_else(|_| panic!("missing snapshot {}", path.display()));
+    let (a, b) = (parse(&expected), parse(&actual));
+    let matches = a.len() == b.len()
+        && a.iter().zip(&b).all(|((name_a, a), (name_b, b))| {
+            name_a == name_b
+                && a.len() == b.len()
+                && a.iter().zip(b).all(|(a, b)| (a - b).abs() <= TOLERANCE)
+        });
+    assert!(
+        matches,
+        "layout differs from snapshot {name}\n--- expected\n{expected}--- actual\n{actual}"
+    );
+}
+
+#[test]
+fn draw_to_hand_layout() {
+    let mut table = TestTable::new(1).with_deck(1, test_deck(5));
+
+    draw(&mut table, 3, 1);
+
+    assert_snapshot("draw_to_hand", &mut table);
+}
+
+#[test]
+fn discard_stacks_on_the_deck() {
+    let mut table = TestTable::new(1).with_deck(1, test_deck(3));
+    draw(&mut table, 3, 1);
+
+    discard(&mut table, "card-3");
+    discard(&mut table, "card-2");
+
+    assert_snapshot("discard", &mut table);
+}
+
+#[test]
+fn align_after_discard_closes_the_gap() {
+    let mut table = TestTable::new(1).with_deck(1, test_deck(5));
+    draw(&mut table, 3, 1);
+    discard(&mut table, "card-4");
+
+    table
+        .send(AlignCardsInHand {
+            player: 1,
+            timing: None,
+        })
+        .settle();
+
+    // the hand area of player 1 is at (20, 0, 10), its cards are laid out around it
+    let hand = table.hand(1);
+    for (name, transform) in table.card_transforms() {
+        if hand.contains(&name) {
+            let offset = transform.translation - Vec3::new(20.0, 0.0, 10.0);
+            assert!(
+                offset.x.abs() <= DECK_WIDTH / 2.0 && offset.z.abs() < TOLERANCE,
+                "{name} is at {}, away from the hand",
+                transform.translation
+            );
+        }
+    }
+
+    assert_snapshot("align", &mut table);
+}
+
+/// A hand only tilted towards its player, like the one in the example, lays its cards out
+/// where a flat hand does; only hands turned to face another side fan along their own axis.
+#[test]
+fn a_tilted_hand_keeps_the_flat_layout() {
+    let mut table = TestTable::new(1).with_deck(1, test_deck(5));
+    let mut hands = table.app.world_mut().query::<(&HandArea, &mut Transform)>();
+    for (_, mut transform) in hands.iter_mut(table.app.world_mut()) {
+        transform.rotate_x(std::f32::consts::FRAC_PI_4);
+    }
+
+    draw(&mut table, 3, 1);
+
+    let hand = table.hand(1);
+    let transforms = table.card_transforms();
+    for (slot, name) in hand.iter().enumerate() {
+        let (_, transform) = transforms.iter().find(|(n, _)| n == name).unwrap();
+        let flat = Vec3::new(20.0 + slot as f32 * 2.6 - DECK_WIDTH / 2.0, 0.0, 10.0);
+        assert!(
+            transform.translation.distance(flat) < TOLERANCE,
+            "{name} is at {}, not {flat}",
+            transform.translation
+        );
+    }
+}
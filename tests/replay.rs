@@ -0,0 +1,207 @@
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_la_mesa::attach::{AttachCard, AttachSide, DetachCard};
+use bevy_la_mesa::events::{
+    ClearDeck, DeckShuffle, DiscardCardToDeck, DrawToHand, PlaceCardOnTable, RenderDeck,
+    ReorderDeck, ReplaceDeck,
+};
+use bevy_la_mesa::peek::{MovePeekedCard, PeekDeck, Peeking, ResolvePeek};
+use bevy_la_mesa::replay::{Replay, ReplayPlayer, ReplayPlugin, ReplayRecorder};
+use bevy_la_mesa::shuffle::{ReorderOp, ShuffleStyle, TableRng};
+use bevy_la_mesa::testing::{test_deck, TestCard, TestTable};
+use std::time::Duration;
+
+const SEED: u64 = 42;
+
+/// Frames of 16ms on both tables, so the commands are played back in the frames they were
+/// recorded in.
+fn fixed_frames(table: &mut TestTable) {
+    table
+        .app
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            16,
+        )));
+}
+
+type Layout = (
+    Vec<String>,
+    Vec<String>,
+    Vec<String>,
+    Vec<String>,
+    Vec<(String, Transform)>,
+);
+
+fn layout(table: &mut TestTable) -> Layout {
+    (
+        table.hand(1),
+        table.deck_cards(1),
+        table.deck_cards(2),
+        table.play_area_cards(1, 1),
+        table.card_transforms(),
+    )
+}
+
+fn spares(n: usize) -> Vec<TestCard> {
+    (1..=n)
+        .map(|i| TestCard {
+            name: format!("spare-{i}"),
+            value: i as i64,
+        })
+        .collect()
+}
+
+/// Plays a match on a recorded table and returns the replay and the layout it ended in.
+fn record() -> (Replay<TestCard>, Layout) {
+    let mut table = TestTable::with_plugins(1, ReplayPlugin::<TestCard>::default());
+    table
+        .app
+        .insert_resource(TableRng::seeded(SEED))
+        .init_resource::<ReplayRecorder<TestCard>>();
+    fixed_frames(&mut table);
+    let mut table = table
+        .with_deck(1, test_deck(8))
+        .with_deck(2, spares(2))
+        .with_play_area(1, 1);
+    let deck_entity = table.deck(1);
+
+    table
+        .send(DeckShuffle {
+            deck_entity,
+            style: ShuffleStyle::Riffle,
+            seed: None,
+            timing: None,
+        })
+        .settle();
+    table
+        .send(DrawToHand {
+            deck_entity,
+            num_cards: 3,
+            player: 1,
+            timing: None,
+        })
+        .settle();
+
+    let hand: Vec<Entity> = table.query(|table| table.hand(1).iter().map(|(e, _)| *e).collect());
+    table
+        .send(PlaceCardOnTable {
+            card_entity: hand[0],
+            marker: 1,
+            player: 1,
+            timing: None,
+        })
+        .settle();
+    table
+        .send(AttachCard {
+            card_entity: hand[1],
+            host_entity: hand[0],
+            side: AttachSide::Over,
+            timing: None,
+        })
+        .settle();
+
+    // the sort waits for the peek, so it sorts the deck the peek leaves behind
+    table
+        .send(PeekDeck {
+            deck_entity,
+            count: 3,
+            player: 1,
+            timing: None,
+        })
+        .update()
+        .update();
+    table
+        .send(ReorderDeck {
+            deck_entity,
+            op: ReorderOp::SortByKey(|card: &TestCard| card.value % 3),
+            timing: None,
+        })
+        .update();
+    let fan = table
+        .app
+        .world()
+        .get::<Peeking>(deck_entity)
+        .unwrap()
+        .cards
+        .clone();
+    table
+        .send(MovePeekedCard {
+            deck_entity,
+            card_entity: fan[2],
+            position: 0,
+        })
+        .update();
+    table
+        .send(ResolvePeek {
+            deck_entity,
+            bottom: vec![fan[1]],
+        })
+        .settle();
+
+    table.send(DetachCard {
+        card_entity: hand[1],
+    });
+    table
+        .send(DiscardCardToDeck {
+            card_entity: hand[2],
+            deck_entity,
+            timing: None,
+        })
+        .settle();
+    let spare_deck = table.deck(2);
+    table
+        .send(ReplaceDeck {
+            deck_entity: spare_deck,
+            deck: spares(3),
+        })
+        .settle();
+    table
+        .send(ClearDeck {
+            deck_entity: spare_deck,
+            animate: false,
+        })
+        .settle();
+    table
+        .send(RenderDeck {
+            deck_entity: spare_deck,
+            deck: spares(1),
+        })
+        .settle();
+    assert!(table.errors().is_empty());
+
+    let replay = table
+        .app
+        .world()
+        .resource::<ReplayRecorder<TestCard>>()
+        .replay()
+        .clone();
+    (replay, layout(&mut table))
+}
+
+#[test]
+fn a_replay_ends_in_the_recorded_layout() {
+    let (replay, recorded) = record();
+    assert_eq!(replay.seed, SEED);
+    // the spare deck rendered again after it was cleared is played back in its turn
+    assert_eq!(replay.decks.len(), 2);
+    let json = serde_json::to_string(&replay).unwrap();
+    let replay: Replay<TestCard> = serde_json::from_str(&json).unwrap();
+
+    let mut table = TestTable::with_plugins(1, ReplayPlugin::<TestCard>::default())
+        .with_deck(1, vec![])
+        .with_deck(2, vec![])
+        .with_play_area(1, 1);
+    table.app.insert_resource(ReplayPlayer::new(replay));
+    fixed_frames(&mut table);
+    while !table
+        .app
+        .world()
+        .resource::<ReplayPlayer<TestCard>>()
+        .finished()
+    {
+        table.update();
+    }
+    table.settle();
+
+    assert_eq!(layout(&mut table), recorded);
+    assert!(table.errors().is_empty());
+}
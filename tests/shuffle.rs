@@ -0,0 +1,148 @@
+use bevy::prelude::*;
+use bevy_la_mesa::events::{DeckShuffle, DeckShuffled, DrawToHand};
+use bevy_la_mesa::shuffle::ShuffleStyle;
+use bevy_la_mesa::testing::{test_deck, TestTable};
+
+const CARDS: usize = 20;
+
+/// Positions in the deck before the shuffle of the cards after it, both top card first.
+fn shuffle(style: ShuffleStyle, seed: u64) -> Vec<usize> {
+    let mut table = TestTable::new(1).with_deck(1, test_deck(CARDS));
+    let before = table.deck_cards(1);
+    let deck_entity = table.deck(1);
+    table
+        .send(DeckShuffle {
+            deck_entity,
+            style,
+            seed: Some(seed),
+            timing: None,
+        })
+        .update();
+
+    // the order the shuffle planned, reported when it starts
+    let planned: Vec<Entity> = {
+        let messages = table.app.world().resource::<Messages<DeckShuffled>>();
+        let shuffled: Vec<_> = messages
+            .get_cursor()
+            .read(messages)
+            .map(|m| m.cards.clone())
+            .collect();
+        assert_eq!(shuffled.len(), 1);
+        shuffled[0].clone()
+    };
+
+    table.settle();
+    let after = table.deck_cards(1);
+    let entities: Vec<Entity> = after.iter().map(|name| table.card(name)).collect();
+    assert_eq!(entities, planned, "{style:?} did not end in its plan");
+
+    let mut order: Vec<usize> = after
+        .iter()
+        .map(|name| before.iter().position(|b| b == name).unwrap())
+        .collect();
+    let positions = order.clone();
+    order.sort();
+    assert_eq!(
+        order,
+        (0..CARDS).collect::<Vec<_>>(),
+        "{style:?} lost cards"
+    );
+    positions
+}
+
+/// Whether the cards taken from `from` keep their relative order in `order`.
+fn keeps_order(order: &[usize], from: impl Fn(usize) -> bool) -> bool {
+    let kept: Vec<usize> = order.iter().copied().filter(|i| from(*i)).collect();
+    kept.is_sorted()
+}
+
+#[test]
+fn every_style_ends_in_the_order_it_planned() {
+    for style in [
+        ShuffleStyle::Alternate,
+        ShuffleStyle::Riffle,
+        ShuffleStyle::Overhand,
+        ShuffleStyle::Cut,
+        ShuffleStyle::Wash,
+    ] {
+        for seed in 0..5 {
+            let order = shuffle(style, seed);
+            assert_eq!(order, shuffle(style, seed), "{style:?} is not repeatable");
+        }
+    }
+}
+
+#[test]
+fn a_cut_moves_the_top_part_under_the_bottom_part() {
+    for seed in 0..5 {
+        let order = shuffle(ShuffleStyle::Cut, seed);
+        let split = order[0];
+        assert!(split > 0);
+        let expected: Vec<usize> = (split..CARDS).chain(0..split).collect();
+        assert_eq!(order, expected);
+    }
+}
+
+#[test]
+fn a_riffle_interleaves_two_halves() {
+    for seed in 0..5 {
+        let order = shuffle(ShuffleStyle::Riffle, seed);
+        let interleaves = (1..CARDS)
+            .any(|split| keeps_order(&order, |i| i < split) && keeps_order(&order, |i| i >= split));
+        assert!(interleaves, "{order:?}");
+    }
+}
+
+#[test]
+fn an_overhand_reverses_the_packets() {
+    for seed in 0..5 {
+        let order = shuffle(ShuffleStyle::Overhand, seed);
+
+        // runs of cards that were together, laid back in reverse give the old deck
+        let mut packets: Vec<Vec<usize>> = vec![];
+        for i in order {
+            match packets.last_mut() {
+                Some(packet) if packet.last() == Some(&(i.wrapping_sub(1))) => packet.push(i),
+                _ => packets.push(vec![i]),
+            }
+        }
+        assert!(packets.len() > 1);
+        let restored: Vec<usize> = packets.into_iter().rev().flatten().collect();
+        assert_eq!(restored, (0..CARDS).collect::<Vec<_>>());
+    }
+}
+
+/// Operations sent in the same frame take their place in the queue in the order the plugin
+/// handles them, so the shuffle goes first even though the draw was sent before it.
+#[test]
+fn a_draw_sent_with_a_shuffle_draws_from_the_shuffled_deck() {
+    let mut table = TestTable::new(1).with_deck(1, test_deck(CARDS));
+    let deck_entity = table.deck(1);
+    table
+        .send(DrawToHand {
+            deck_entity,
+            num_cards: 3,
+            player: 1,
+            timing: None,
+        })
+        .send(DeckShuffle {
+            deck_entity,
+            style: ShuffleStyle::Riffle,
+            seed: Some(7),
+            timing: None,
+        })
+        .update();
+
+    let planned: Vec<Entity> = {
+        let messages = table.app.world().resource::<Messages<DeckShuffled>>();
+        messages
+            .get_cursor()
+            .read(messages)
+            .flat_map(|m| m.cards.clone())
+            .collect()
+    };
+    table.settle();
+
+    let drawn: Vec<Entity> = table.hand(1).iter().map(|name| table.card(name)).collect();
+    assert_eq!(drawn, planned[..3]);
+}
@@ -0,0 +1,337 @@
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_la_mesa::animation::AnimationSettings;
+use bevy_la_mesa::attach::AttachSide;
+use bevy_la_mesa::events::DealOrder;
+use bevy_la_mesa::peek::Peeking;
+use bevy_la_mesa::shuffle::ShuffleStyle;
+use bevy_la_mesa::sync::{DeckReorder, LoopbackTransport, SyncPlugin, TableCommand, TableSync};
+use bevy_la_mesa::testing::{test_deck, TestCard, TestTable};
+use bevy_la_mesa::{Card, CardId};
+use std::time::Duration;
+
+const HOST: usize = 1;
+const CLIENT: usize = 2;
+const OTHER_CLIENT: usize = 3;
+
+type Command = TableCommand<TestCard>;
+
+/// Name of every card in a zone, in order, as one peer knows them.
+type Zone = Vec<(CardId, String)>;
+
+fn table(sync: TableSync<TestCard>) -> TestTable {
+    let mut table = TestTable::with_plugins(3, SyncPlugin::<TestCard>::default())
+        .with_deck(1, vec![])
+        .with_play_area(1, HOST)
+        .with_play_area(1, CLIENT);
+    table.app.insert_resource(sync);
+    table
+}
+
+fn peers() -> (TestTable, TestTable) {
+    let (host_end, client_end) = LoopbackTransport::pair();
+    let host = table(TableSync::host().with_client(CLIENT, host_end));
+    let client = table(TableSync::client(client_end));
+    (host, client)
+}
+
+/// Run both tables until requests, commands and reveals have gone both ways. Peeked decks
+/// never settle, so this runs frames rather than waiting for the tables to be idle.
+fn settle(host: &mut TestTable, client: &mut TestTable) {
+    for _ in 0..50 {
+        host.update();
+        client.update();
+    }
+}
+
+/// Let operations take their time, 100ms a frame.
+fn animated(table: &mut TestTable) {
+    table
+        .app
+        .world_mut()
+        .resource_mut::<AnimationSettings>()
+        .instant = false;
+    table
+        .app
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            100,
+        )));
+}
+
+fn zones(table: &mut TestTable) -> [Zone; 4] {
+    table.query(|table| {
+        let zone = |cards: Vec<(Entity, &TestCard)>| -> Zone {
+            cards
+                .into_iter()
+                .map(|(entity, card)| (table.card_id(entity).unwrap(), card.name.clone()))
+                .collect()
+        };
+        [
+            zone(table.deck(1)),
+            zone(table.hand(HOST)),
+            zone(table.hand(CLIENT)),
+            zone(table.play_area(1, CLIENT)),
+        ]
+    })
+}
+
+/// Cards fanned out by a peek, in fan order.
+fn fan(table: &mut TestTable) -> Zone {
+    let world = table.app.world_mut();
+    let peeking = world
+        .query::<&Peeking>()
+        .single(world)
+        .unwrap()
+        .cards
+        .clone();
+    peeking
+        .into_iter()
+        .map(|entity| {
+            let card = world.get::<Card<TestCard>>(entity).unwrap();
+            (
+                *world.get::<CardId>(entity).unwrap(),
+                card.data.name.clone(),
+            )
+        })
+        .collect()
+}
+
+fn ids(zone: &Zone) -> Vec<CardId> {
+    zone.iter().map(|(id, _)| *id).collect()
+}
+
+fn transforms(table: &mut TestTable) -> Vec<(CardId, Transform)> {
+    let world = table.app.world_mut();
+    let mut q_cards = world.query::<(&CardId, &Transform, &Card<TestCard>)>();
+    let mut cards: Vec<(CardId, Transform)> = q_cards
+        .iter(world)
+        .map(|(id, transform, _)| (*id, *transform))
+        .collect();
+    cards.sort_by_key(|(id, _)| *id);
+    cards
+}
+
+#[test]
+fn clients_are_only_told_the_cards_their_player_sees() {
+    let (mut host, mut client) = peers();
+    host.send(Command::RenderDeck {
+        deck: 1,
+        cards: test_deck(8),
+    });
+    settle(&mut host, &mut client);
+
+    let [host_deck, ..] = zones(&mut host);
+    let [client_deck, ..] = zones(&mut client);
+    assert_eq!(ids(&client_deck), ids(&host_deck));
+    assert!(client_deck.iter().all(|(_, name)| name.is_empty()));
+
+    client.send(Command::DeckShuffle {
+        deck: 1,
+        style: ShuffleStyle::Riffle,
+        seed: None,
+    });
+    host.send(Command::Deal {
+        deck: 1,
+        players: vec![HOST, CLIENT],
+        cards_each: 2,
+        order: DealOrder::RoundRobin,
+    });
+    settle(&mut host, &mut client);
+
+    let [_, host_hand, client_hand, _] = zones(&mut client);
+    let [_, _, own_hand, _] = zones(&mut host);
+    assert_eq!(client_hand, own_hand);
+    assert!(host_hand.iter().all(|(_, name)| name.is_empty()));
+
+    // peeking shows the client the top of the deck, but only while it is its player peeking
+    client.send(Command::PeekDeck {
+        deck: 1,
+        count: 2,
+        player: CLIENT,
+    });
+    settle(&mut host, &mut client);
+    let [host_deck, ..] = zones(&mut host);
+    let [client_deck, ..] = zones(&mut client);
+    let peeked = fan(&mut client);
+    assert_eq!(peeked, fan(&mut host));
+    assert!(peeked.iter().all(|(_, name)| !name.is_empty()));
+    assert_eq!(ids(&client_deck), ids(&host_deck));
+    assert!(client_deck.iter().all(|(_, name)| name.is_empty()));
+
+    let (top, second) = (peeked[0].0, peeked[1].0);
+    client.send(Command::MovePeekedCard {
+        deck: 1,
+        card: second,
+        position: 0,
+    });
+    settle(&mut host, &mut client);
+    client.send(Command::ResolvePeek {
+        deck: 1,
+        bottom: vec![top],
+    });
+    settle(&mut host, &mut client);
+
+    client.send(Command::PlaceCardOnTable {
+        card: client_hand[0].0,
+        marker: 1,
+        player: CLIENT,
+    });
+    settle(&mut host, &mut client);
+    let [_, host_hand, ..] = zones(&mut host);
+    host.send(Command::AttachCard {
+        card: host_hand[0].0,
+        host: client_hand[0].0,
+        side: AttachSide::Under,
+    });
+    settle(&mut host, &mut client);
+
+    let [host_deck, host_hand, own_hand, table_cards] = zones(&mut host);
+    let [client_deck, client_host_hand, client_hand, client_table] = zones(&mut client);
+    assert_eq!(ids(&client_deck), ids(&host_deck));
+    // the peeked cards went back on top and under the deck, and the client remembers them
+    assert_eq!(client_deck.first(), host_deck.first());
+    assert_eq!(client_deck.last(), host_deck.last());
+    assert_eq!(ids(&client_host_hand), ids(&host_hand));
+    assert_eq!(client_hand, own_hand);
+    assert_eq!(client_table, table_cards);
+    assert_eq!(table_cards.len(), 2);
+    assert_eq!(transforms(&mut client), transforms(&mut host));
+    assert!(host.errors().is_empty());
+    assert!(client.errors().is_empty());
+}
+
+#[test]
+fn a_shuffle_makes_the_client_forget_the_cards_of_the_deck() {
+    let (mut host, mut client) = peers();
+    host.send(Command::RenderDeck {
+        deck: 1,
+        cards: test_deck(4),
+    });
+    settle(&mut host, &mut client);
+
+    // the card is seen on the table, then goes back into the deck
+    host.send(Command::DrawToTable {
+        deck: 1,
+        play_area_markers: vec![1],
+        player: CLIENT,
+    });
+    settle(&mut host, &mut client);
+    let [.., client_table] = zones(&mut client);
+    let card = client_table[0].0;
+    assert_eq!(client_table, [(card, "card-4".to_string())]);
+
+    host.send(Command::DiscardCardToDeck { card, deck: 1 });
+    settle(&mut host, &mut client);
+    host.send(Command::DeckShuffle {
+        deck: 1,
+        style: ShuffleStyle::Alternate,
+        seed: None,
+    });
+    settle(&mut host, &mut client);
+
+    let [host_deck, ..] = zones(&mut host);
+    let [client_deck, ..] = zones(&mut client);
+    assert_eq!(ids(&client_deck), ids(&host_deck));
+    assert!(client_deck.iter().all(|(_, name)| name.is_empty()));
+    let mut names: Vec<String> = host_deck.into_iter().map(|(_, name)| name).collect();
+    names.sort();
+    assert_eq!(names, ["card-1", "card-2", "card-3", "card-4"]);
+}
+
+#[test]
+fn a_reveal_on_the_host_only_reaches_the_clients_that_see_the_card() {
+    let (host_end, client_end) = LoopbackTransport::pair();
+    let (host_other_end, other_end) = LoopbackTransport::pair();
+    let mut host = table(
+        TableSync::host()
+            .with_client(CLIENT, host_end)
+            .with_client(OTHER_CLIENT, host_other_end),
+    );
+    let mut client = table(TableSync::client(client_end));
+    let mut other = table(TableSync::client(other_end));
+    let settle = |host: &mut TestTable, client: &mut TestTable, other: &mut TestTable| {
+        for _ in 0..50 {
+            host.update();
+            client.update();
+            other.update();
+        }
+    };
+
+    host.send(Command::RenderDeck {
+        deck: 1,
+        cards: test_deck(4),
+    })
+    .send(Command::DrawToHand {
+        deck: 1,
+        num_cards: 1,
+        player: CLIENT,
+    });
+    settle(&mut host, &mut client, &mut other);
+    let [_, _, client_hand, _] = zones(&mut client);
+    let card = client_hand[0].0;
+
+    let secret = TestCard {
+        name: "secret".to_string(),
+        value: 7,
+    };
+    host.send(Command::RevealCard {
+        card,
+        data: secret.clone(),
+    });
+    settle(&mut host, &mut client, &mut other);
+
+    let [_, _, host_view, _] = zones(&mut host);
+    let [_, _, client_hand, _] = zones(&mut client);
+    let [_, _, other_view, _] = zones(&mut other);
+    assert_eq!(host_view, [(card, "secret".to_string())]);
+    assert_eq!(client_hand, host_view);
+    assert_eq!(other_view, [(card, String::new())]);
+    assert!(other.errors().is_empty());
+}
+
+#[test]
+fn commands_naming_the_cards_of_a_queued_render_wait_for_it() {
+    let (mut host, mut client) = peers();
+    host.send(Command::RenderDeck {
+        deck: 1,
+        cards: test_deck(4),
+    });
+    settle(&mut host, &mut client);
+    animated(&mut host);
+    animated(&mut client);
+
+    // the new deck waits for the shuffle, its cards get the ids after the four above
+    let extra = |name: &str| TestCard {
+        name: name.to_string(),
+        value: 5,
+    };
+    host.send(Command::DeckShuffle {
+        deck: 1,
+        style: ShuffleStyle::Alternate,
+        seed: None,
+    })
+    .send(Command::ReplaceDeck {
+        deck: 1,
+        cards: vec![extra("bottom"), extra("top")],
+    })
+    .send(Command::ReorderDeck {
+        deck: 1,
+        op: DeckReorder::ToTop(CardId(4)),
+    });
+    for _ in 0..4 {
+        settle(&mut host, &mut client);
+    }
+
+    let [host_deck, ..] = zones(&mut host);
+    let [client_deck, ..] = zones(&mut client);
+    assert_eq!(
+        host_deck,
+        [
+            (CardId(4), "bottom".to_string()),
+            (CardId(5), "top".to_string())
+        ]
+    );
+    assert_eq!(ids(&client_deck), ids(&host_deck));
+    assert!(host.errors().is_empty());
+    assert!(client.errors().is_empty());
+}
@@ -0,0 +1,753 @@
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_la_mesa::animation::AnimationSettings;
+use bevy_la_mesa::attach::{AttachCard, AttachSide, Attached, DetachCard};
+use bevy_la_mesa::events::{
+    AlignCardsInHand, CardHover, CardPress, ClearDeck, Deal, DealOrder, DespawnAllCards,
+    DiscardCardToDeck, DrawToHand, DrawToTable, LaMesaError, PlaceCardOnTable, ReorderDeck,
+    ReplaceDeck,
+};
+use bevy_la_mesa::highlight::Dimmed;
+use bevy_la_mesa::peek::{PeekDeck, Peeking, ResolvePeek};
+use bevy_la_mesa::rules::{CardRules, TableRules};
+use bevy_la_mesa::shuffle::ReorderOp;
+use bevy_la_mesa::table::TableQuery;
+use bevy_la_mesa::testing::{test_deck, TestCard, TestTable};
+use bevy_la_mesa::{Card, CardZone, HandArea, HoverStyle, PlayArea};
+use std::time::Duration;
+
+fn draw(table: &mut TestTable, num_cards: usize, player: usize) {
+    let deck_entity = table.deck(1);
+    table
+        .send(DrawToHand {
+            deck_entity,
+            num_cards,
+            player,
+            timing: None,
+        })
+        .settle();
+}
+
+/// Let operations take their time, 16ms a frame; the cards themselves do not move.
+fn animated(table: &mut TestTable) {
+    table
+        .app
+        .world_mut()
+        .resource_mut::<AnimationSettings>()
+        .instant = false;
+    table
+        .app
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            16,
+        )));
+}
+
+/// Draw the top two cards, then discard both back while the first one is still flying.
+fn discard_two(table: &mut TestTable) {
+    draw(table, 2, 1);
+    animated(table);
+
+    let deck_entity = table.deck(1);
+    for name in ["card-3", "card-2"] {
+        let card_entity = table.card(name);
+        table.send(DiscardCardToDeck {
+            card_entity,
+            deck_entity,
+            timing: None,
+        });
+    }
+}
+
+#[test]
+fn decks_sent_before_the_first_frame_render_once() {
+    let mut table = TestTable::new(1).with_deck(1, test_deck(3));
+
+    table.assert_deck(1, ["card-3", "card-2", "card-1"]);
+    assert_eq!(table.query(|table| table.index().len()), 3);
+}
+
+#[test]
+fn draw_to_hand_takes_the_top_cards() {
+    let mut table = TestTable::new(1).with_deck(1, test_deck(5));
+
+    draw(&mut table, 3, 1);
+
+    table.assert_hand(1, ["card-5", "card-4", "card-3"]);
+    table.assert_deck(1, ["card-2", "card-1"]);
+}
+
+#[test]
+fn draw_to_hand_fills_up_to_the_hand_size() {
+    let mut table = TestTable::new(1).with_deck(1, test_deck(5));
+
+    draw(&mut table, 2, 1);
+    draw(&mut table, 3, 1);
+
+    table.assert_hand(1, ["card-5", "card-4", "card-3"]);
+}
+
+#[test]
+fn deal_round_robin() {
+    let mut table = TestTable::new(2).with_deck(1, test_deck(6));
+
+    let deck_entity = table.deck(1);
+    table
+        .send(Deal {
+            deck_entity,
+            players: vec![1, 2],
+            cards_each: 2,
+            order: DealOrder::RoundRobin,
+            timing: None,
+        })
+        .settle();
+
+    table.assert_hand(1, ["card-6", "card-4"]);
+    table.assert_hand(2, ["card-5", "card-3"]);
+    table.assert_deck(1, ["card-2", "card-1"]);
+}
+
+#[test]
+fn place_and_discard() {
+    let mut table = TestTable::new(1)
+        .with_deck(1, test_deck(3))
+        .with_play_area(1, 1);
+    draw(&mut table, 2, 1);
+
+    let card_entity = table.card("card-3");
+    table
+        .send(PlaceCardOnTable {
+            card_entity,
+            marker: 1,
+            player: 1,
+            timing: None,
+        })
+        .settle();
+    table.assert_hand(1, ["card-2"]);
+    table.assert_play_area(1, 1, ["card-3"]);
+
+    let deck_entity = table.deck(1);
+    table
+        .send(DiscardCardToDeck {
+            card_entity,
+            deck_entity,
+            timing: None,
+        })
+        .settle();
+    table.assert_play_area(1, 1, Vec::<&str>::new());
+    table.assert_deck(1, ["card-3", "card-1"]);
+}
+
+#[test]
+fn placed_cards_hover_over_their_play_area() {
+    let mut table = TestTable::new(1)
+        .with_deck(1, test_deck(3))
+        .with_play_area(1, 1);
+    draw(&mut table, 2, 1);
+    let play_area = table.play_area(1, 1);
+    table
+        .app
+        .world_mut()
+        .entity_mut(play_area)
+        .insert(HoverStyle::Lift(Vec3::Y));
+
+    let card_entity = table.card("card-3");
+    table
+        .send(PlaceCardOnTable {
+            card_entity,
+            marker: 1,
+            player: 1,
+            timing: None,
+        })
+        .settle();
+    table.send(CardHover {
+        entity: card_entity,
+    });
+    table.settle();
+
+    let transform = table.app.world().get::<Transform>(card_entity).unwrap();
+    assert!(
+        transform
+            .translation
+            .abs_diff_eq(Vec3::new(20.0, 1.0, -5.0), 1e-4),
+        "card-3 hovers at {}",
+        transform.translation
+    );
+}
+
+#[test]
+fn selecting_a_placed_card_raises_it_over_its_play_area() {
+    let mut table = TestTable::new(1)
+        .with_deck(1, test_deck(3))
+        .with_play_area(1, 1);
+    draw(&mut table, 2, 1);
+
+    let card_entity = table.card("card-3");
+    table
+        .send(PlaceCardOnTable {
+            card_entity,
+            marker: 1,
+            player: 1,
+            timing: None,
+        })
+        .settle();
+    table
+        .send(CardPress {
+            entity: card_entity,
+        })
+        .settle();
+
+    let transform = table.app.world().get::<Transform>(card_entity).unwrap();
+    assert!(
+        transform
+            .translation
+            .abs_diff_eq(Vec3::new(20.0, 0.5, -5.0), 1e-4),
+        "card-3 is raised to {}",
+        transform.translation
+    );
+}
+
+#[test]
+fn draw_to_table() {
+    let mut table = TestTable::new(1)
+        .with_deck(1, test_deck(3))
+        .with_play_area(1, 1)
+        .with_play_area(2, 1);
+
+    let deck_entity = table.deck(1);
+    table
+        .send(DrawToTable {
+            deck_entity,
+            play_area_markers: vec![1, 2],
+            player: 1,
+            timing: None,
+        })
+        .settle();
+
+    table.assert_play_area(1, 1, ["card-3"]);
+    table.assert_play_area(2, 1, ["card-2"]);
+    table.assert_deck(1, ["card-1"]);
+}
+
+#[test]
+fn reorder_deck() {
+    let mut table = TestTable::new(1).with_deck(1, test_deck(4));
+
+    let deck_entity = table.deck(1);
+    table
+        .send(ReorderDeck::<TestCard> {
+            deck_entity,
+            op: ReorderOp::Reverse,
+            timing: None,
+        })
+        .settle();
+    table.assert_deck(1, ["card-1", "card-2", "card-3", "card-4"]);
+
+    table
+        .send(ReorderDeck::<TestCard> {
+            deck_entity,
+            op: ReorderOp::Cut(1),
+            timing: None,
+        })
+        .settle();
+    table.assert_deck(1, ["card-2", "card-3", "card-4", "card-1"]);
+}
+
+#[test]
+fn peeked_cards_leave_the_deck_until_resolved() {
+    let mut table = TestTable::new(1).with_deck(1, test_deck(5));
+    let deck_entity = table.deck(1);
+
+    table
+        .send(PeekDeck {
+            deck_entity,
+            count: 2,
+            player: 1,
+            timing: None,
+        })
+        .update();
+    table.assert_deck(1, ["card-3", "card-2", "card-1"]);
+
+    let card_entity = table.card("card-5");
+    table
+        .send(ResolvePeek {
+            deck_entity,
+            bottom: vec![card_entity, card_entity],
+        })
+        .update();
+    assert_eq!(table.errors(), [LaMesaError::DuplicateCard { card_entity }]);
+
+    table
+        .send(ResolvePeek {
+            deck_entity,
+            bottom: vec![card_entity],
+        })
+        .settle();
+    table.assert_deck(1, ["card-4", "card-3", "card-2", "card-1", "card-5"]);
+}
+
+fn peek_two(table: &mut TestTable) {
+    let deck_entity = table.deck(1);
+    table
+        .send(PeekDeck {
+            deck_entity,
+            count: 2,
+            player: 1,
+            timing: None,
+        })
+        .update();
+}
+
+#[test]
+fn clearing_a_peeked_deck_ends_the_peek() {
+    let mut table = TestTable::new(1).with_deck(1, test_deck(3));
+    peek_two(&mut table);
+
+    let deck_entity = table.deck(1);
+    table
+        .send(ClearDeck {
+            deck_entity,
+            animate: false,
+        })
+        .settle();
+
+    assert!(table.errors().is_empty());
+    assert!(table.query(|table| table.index().is_empty()));
+    assert!(table.app.world().get::<Peeking>(deck_entity).is_none());
+}
+
+#[test]
+fn replacing_a_peeked_deck_ends_the_peek() {
+    let mut table = TestTable::new(1).with_deck(1, test_deck(3));
+    peek_two(&mut table);
+
+    let deck_entity = table.deck(1);
+    let mut deck = test_deck(2);
+    for card in deck.iter_mut() {
+        card.name = format!("new-{}", card.value);
+    }
+    table.send(ReplaceDeck { deck_entity, deck }).settle();
+
+    table.assert_deck(1, ["new-2", "new-1"]);
+}
+
+#[test]
+fn despawning_all_cards_ends_peeks() {
+    let mut table = TestTable::new(1).with_deck(1, test_deck(3));
+    peek_two(&mut table);
+
+    table.send(DespawnAllCards { animate: false }).settle();
+
+    assert!(table.query(|table| table.index().is_empty()));
+}
+
+#[test]
+fn align_keeps_the_order_of_the_hand() {
+    let mut table = TestTable::new(1).with_deck(1, test_deck(4));
+    draw(&mut table, 3, 1);
+
+    table
+        .send(AlignCardsInHand {
+            player: 1,
+            timing: None,
+        })
+        .settle();
+
+    table.assert_hand(1, ["card-4", "card-3", "card-2"]);
+}
+
+struct EvenCardsOnly;
+
+impl CardRules<TestCard> for EvenCardsOnly {
+    fn can_play(&self, card: &TestCard, _area: &PlayArea, _table: &TableQuery<TestCard>) -> bool {
+        card.value % 2 == 0
+    }
+}
+
+#[test]
+fn rules_refuse_illegal_plays() {
+    let mut table = TestTable::new(1)
+        .with_deck(1, test_deck(3))
+        .with_play_area(1, 1);
+    table
+        .app
+        .insert_resource(TableRules::<TestCard>::new(EvenCardsOnly));
+    draw(&mut table, 2, 1);
+
+    for name in ["card-3", "card-2"] {
+        let card_entity = table.card(name);
+        table
+            .send(PlaceCardOnTable {
+                card_entity,
+                marker: 1,
+                player: 1,
+                timing: None,
+            })
+            .settle();
+    }
+
+    table.assert_hand(1, ["card-3"]);
+    table.assert_play_area(1, 1, ["card-2"]);
+}
+
+struct OneCardPerArea;
+
+impl CardRules<TestCard> for OneCardPerArea {
+    fn can_play(&self, _card: &TestCard, area: &PlayArea, table: &TableQuery<TestCard>) -> bool {
+        table.play_area(area.marker, area.player).is_empty()
+    }
+}
+
+#[test]
+fn rules_see_the_moves_queued_before() {
+    let mut table = TestTable::new(1)
+        .with_deck(1, test_deck(3))
+        .with_play_area(1, 1);
+    table
+        .app
+        .insert_resource(TableRules::<TestCard>::new(OneCardPerArea));
+    draw(&mut table, 2, 1);
+
+    // both plays are read in the same frame, the second one starts after the first
+    let (card_3, card_2) = (table.card("card-3"), table.card("card-2"));
+    for card_entity in [card_3, card_2] {
+        table.send(PlaceCardOnTable {
+            card_entity,
+            marker: 1,
+            player: 1,
+            timing: None,
+        });
+    }
+    table.settle();
+
+    assert_eq!(
+        table.errors(),
+        [LaMesaError::PlayRefused {
+            card_entity: card_2,
+            marker: 1,
+            player: 1,
+        }]
+    );
+    table.assert_hand(1, ["card-2"]);
+    table.assert_play_area(1, 1, ["card-3"]);
+}
+
+#[test]
+fn unplayable_cards_are_dimmed_without_touching_the_game_dimming() {
+    let mut table = TestTable::new(1)
+        .with_deck(1, test_deck(4))
+        .with_play_area(1, 1);
+    table
+        .app
+        .insert_resource(TableRules::<TestCard>::new(EvenCardsOnly));
+    draw(&mut table, 3, 1);
+    let (card_4, card_3, card_2) = (
+        table.card("card-4"),
+        table.card("card-3"),
+        table.card("card-2"),
+    );
+    table.app.world_mut().entity_mut(card_4).insert(Dimmed);
+    table.update();
+
+    let dimmed = |table: &TestTable, entity| table.app.world().get::<Dimmed>(entity).is_some();
+    assert!(dimmed(&table, card_4));
+    assert!(dimmed(&table, card_3));
+    assert!(!dimmed(&table, card_2));
+
+    // every card becomes playable, only the one the plugin dimmed lights up again
+    table
+        .app
+        .insert_resource(TableRules::<TestCard>::new(OneCardPerArea));
+    table.update();
+    assert!(dimmed(&table, card_4));
+    assert!(!dimmed(&table, card_3));
+}
+
+#[test]
+fn card_ids_follow_the_cards() {
+    let mut table = TestTable::new(1).with_deck(1, test_deck(2));
+    let id = table.card_id("card-2");
+
+    draw(&mut table, 1, 1);
+
+    let zone = table.query(move |table| table.index().zone(id));
+    assert_eq!(zone, Some(CardZone::Hand { player: 1 }));
+}
+
+#[test]
+fn attachments_follow_their_host() {
+    let mut table = TestTable::new(1)
+        .with_deck(1, test_deck(3))
+        .with_play_area(1, 1)
+        .with_play_area(2, 1);
+    draw(&mut table, 2, 1);
+
+    let host_entity = table.card("card-3");
+    table
+        .send(PlaceCardOnTable {
+            card_entity: host_entity,
+            marker: 1,
+            player: 1,
+            timing: None,
+        })
+        .settle();
+    let card_entity = table.card("card-2");
+    table
+        .send(AttachCard {
+            card_entity,
+            host_entity,
+            side: AttachSide::Under,
+            timing: None,
+        })
+        .settle();
+    table.assert_hand(1, Vec::<&str>::new());
+    table.assert_play_area(1, 1, ["card-2", "card-3"]);
+
+    table
+        .send(PlaceCardOnTable {
+            card_entity: host_entity,
+            marker: 2,
+            player: 1,
+            timing: None,
+        })
+        .settle();
+    table.assert_play_area(1, 1, Vec::<&str>::new());
+    table.assert_play_area(2, 1, ["card-2", "card-3"]);
+
+    // tucked under the host on play area 2, and resting there
+    let expected = Vec3::new(20.0, -0.005, -10.5);
+    let world = table.app.world();
+    let transform = world.get::<Transform>(card_entity).unwrap();
+    let resting = world.get::<Card<TestCard>>(card_entity).unwrap().transform;
+    assert!(transform.translation.abs_diff_eq(expected, 1e-4));
+    assert!(resting.is_some_and(|resting| resting.translation.abs_diff_eq(expected, 1e-4)));
+}
+
+/// Placing the host again on the play area it is on, here after the play area moved, takes
+/// its attachments along as much as moving it to another play area does.
+#[test]
+fn attachments_follow_their_host_placed_on_its_own_play_area() {
+    let mut table = TestTable::new(1)
+        .with_deck(1, test_deck(3))
+        .with_play_area(1, 1);
+    draw(&mut table, 2, 1);
+
+    let host_entity = table.card("card-3");
+    let place = |table: &mut TestTable| {
+        table
+            .send(PlaceCardOnTable {
+                card_entity: host_entity,
+                marker: 1,
+                player: 1,
+                timing: None,
+            })
+            .settle();
+    };
+    place(&mut table);
+    let card_entity = table.card("card-2");
+    table
+        .send(AttachCard {
+            card_entity,
+            host_entity,
+            side: AttachSide::Under,
+            timing: None,
+        })
+        .settle();
+
+    let play_area = table.play_area(1, 1);
+    table
+        .app
+        .world_mut()
+        .get_mut::<Transform>(play_area)
+        .unwrap()
+        .translation = Vec3::new(0.0, 0.0, 5.0);
+    place(&mut table);
+
+    table.assert_play_area(1, 1, ["card-2", "card-3"]);
+    let expected = Vec3::new(0.0, -0.005, 4.5);
+    let world = table.app.world();
+    let transform = world.get::<Transform>(card_entity).unwrap();
+    let resting = world.get::<Card<TestCard>>(card_entity).unwrap().transform;
+    assert!(world.get::<Attached>(card_entity).is_some());
+    assert!(
+        transform.translation.abs_diff_eq(expected, 1e-4),
+        "{}",
+        transform.translation
+    );
+    assert!(resting.is_some_and(|resting| resting.translation.abs_diff_eq(expected, 1e-4)));
+}
+
+/// An attachment placed on its own comes off its host, even on the play area it is on.
+#[test]
+fn attachments_placed_on_their_own_play_area_are_detached() {
+    let mut table = TestTable::new(1)
+        .with_deck(1, test_deck(3))
+        .with_play_area(1, 1);
+    draw(&mut table, 2, 1);
+
+    let host_entity = table.card("card-3");
+    table
+        .send(PlaceCardOnTable {
+            card_entity: host_entity,
+            marker: 1,
+            player: 1,
+            timing: None,
+        })
+        .settle();
+    let card_entity = table.card("card-2");
+    table
+        .send(AttachCard {
+            card_entity,
+            host_entity,
+            side: AttachSide::Under,
+            timing: None,
+        })
+        .settle();
+    table
+        .send(PlaceCardOnTable {
+            card_entity,
+            marker: 1,
+            player: 1,
+            timing: None,
+        })
+        .settle();
+    assert!(table.app.world().get::<Attached>(card_entity).is_none());
+
+    // the host moving on no longer takes the card along
+    table
+        .app
+        .world_mut()
+        .get_mut::<Transform>(host_entity)
+        .unwrap()
+        .translation = Vec3::new(0.0, 0.0, 5.0);
+    table.update();
+    let transform = table.app.world().get::<Transform>(card_entity).unwrap();
+    assert!(transform
+        .translation
+        .abs_diff_eq(Vec3::new(20.0, 0.0, -5.0), 1e-4));
+}
+
+#[test]
+fn attachments_fill_the_slot_left_by_a_detached_card() {
+    let mut table = TestTable::new(1)
+        .with_deck(1, test_deck(4))
+        .with_play_area(1, 1);
+    draw(&mut table, 4, 1);
+
+    let host_entity = table.card("card-4");
+    table
+        .send(PlaceCardOnTable {
+            card_entity: host_entity,
+            marker: 1,
+            player: 1,
+            timing: None,
+        })
+        .settle();
+    let attach = |table: &mut TestTable, card_entity| {
+        table
+            .send(AttachCard {
+                card_entity,
+                host_entity,
+                side: AttachSide::Over,
+                timing: None,
+            })
+            .settle();
+    };
+    let (card_3, card_2, card_1) = (
+        table.card("card-3"),
+        table.card("card-2"),
+        table.card("card-1"),
+    );
+    attach(&mut table, card_3);
+    attach(&mut table, card_2);
+    table
+        .send(DetachCard {
+            card_entity: card_3,
+        })
+        .settle();
+    attach(&mut table, card_1);
+
+    let slot =
+        |table: &TestTable, entity| table.app.world().get::<Attached>(entity).map(|a| a.slot);
+    assert_eq!(slot(&table, card_3), None);
+    assert_eq!(slot(&table, card_2), Some(2));
+    assert_eq!(slot(&table, card_1), Some(1));
+}
+
+#[test]
+fn hand_order_follows_the_seat() {
+    let mut table = TestTable::new(1).with_deck(1, test_deck(4));
+    // the player sits across the table, so their left is the table's right
+    let world = table.app.world_mut();
+    let mut hand_areas = world.query_filtered::<&mut Transform, With<HandArea>>();
+    for mut transform in hand_areas.iter_mut(world) {
+        transform.rotate_y(std::f32::consts::PI);
+    }
+    draw(&mut table, 3, 1);
+
+    table.assert_hand(1, ["card-4", "card-3", "card-2"]);
+}
+
+#[test]
+fn deck_order_counts_cards_still_flying_to_it() {
+    let mut table = TestTable::new(1).with_deck(1, test_deck(3));
+    draw(&mut table, 1, 1);
+    animated(&mut table);
+
+    let deck_entity = table.deck(1);
+    let card_entity = table.card("card-3");
+    table
+        .send(DiscardCardToDeck {
+            card_entity,
+            deck_entity,
+            timing: None,
+        })
+        .update();
+
+    table.assert_deck(1, ["card-3", "card-2", "card-1"]);
+}
+
+#[test]
+fn clear_deck_waits_for_queued_discards() {
+    let mut table = TestTable::new(1).with_deck(1, test_deck(3));
+    discard_two(&mut table);
+
+    let deck_entity = table.deck(1);
+    table
+        .send(ClearDeck {
+            deck_entity,
+            animate: false,
+        })
+        .settle();
+
+    assert!(table.errors().is_empty());
+    table.assert_deck(1, Vec::<&str>::new());
+    assert!(table.query(|table| table.index().is_empty()));
+}
+
+#[test]
+fn replace_deck_waits_for_queued_discards() {
+    let mut table = TestTable::new(1).with_deck(1, test_deck(3));
+    discard_two(&mut table);
+
+    let deck_entity = table.deck(1);
+    let mut deck = test_deck(2);
+    for card in deck.iter_mut() {
+        card.name = format!("new-{}", card.value);
+    }
+    table.send(ReplaceDeck { deck_entity, deck }).settle();
+
+    assert!(table.errors().is_empty());
+    table.assert_hand(1, Vec::<&str>::new());
+    table.assert_deck(1, ["new-2", "new-1"]);
+}
+
+#[test]
+fn despawn_all_cards_waits_for_queued_operations() {
+    let mut table = TestTable::new(1).with_deck(1, test_deck(3));
+    discard_two(&mut table);
+
+    table.send(DespawnAllCards { animate: false }).settle();
+
+    assert!(table.errors().is_empty());
+    assert!(table.query(|table| table.index().is_empty()));
+}
@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+use bevy_la_mesa::testing::TestTable;
+use bevy_la_mesa::turn::{
+    CurrentPlayer, EndTurn, NextPhase, Phase, ReverseTurnOrder, SkipTurn, TurnPlugin, TurnState,
+};
+
+fn table() -> TestTable {
+    let mut table = TestTable::with_plugins(4, TurnPlugin::default());
+    table.update();
+    table
+}
+
+fn current(table: &TestTable) -> usize {
+    table.app.world().resource::<CurrentPlayer>().0
+}
+
+/// Players whose turn it is after each of `turns` turns ends.
+fn play(table: &mut TestTable, turns: usize) -> Vec<usize> {
+    (0..turns)
+        .map(|_| current(table.send(EndTurn).update()))
+        .collect()
+}
+
+#[test]
+fn reversing_sends_the_turn_back_the_way_it_came() {
+    let mut table = table();
+    assert_eq!(current(&table), 1);
+    assert_eq!(play(&mut table, 2), [2, 3]);
+
+    table.send(ReverseTurnOrder).update();
+    assert_eq!(current(&table), 3);
+    assert_eq!(play(&mut table, 3), [2, 1, 4]);
+}
+
+#[test]
+fn skipped_players_lose_one_turn_per_skip() {
+    let mut table = table();
+    table
+        .send(SkipTurn { player: 3 })
+        .send(SkipTurn { player: 3 })
+        .update();
+
+    assert_eq!(play(&mut table, 8), [2, 4, 1, 2, 4, 1, 2, 3]);
+}
+
+#[test]
+fn ending_the_last_phase_ends_the_turn() {
+    let mut table = table();
+    for _ in 0..3 {
+        table.send(NextPhase).update();
+    }
+    table.update();
+    assert_eq!(
+        *table.app.world().resource::<State<Phase>>().get(),
+        Phase::End
+    );
+    assert_eq!(current(&table), 1);
+
+    table.send(NextPhase).update().update();
+    assert_eq!(
+        *table.app.world().resource::<State<Phase>>().get(),
+        Phase::Draw
+    );
+    assert_eq!(current(&table), 2);
+    assert_eq!(table.app.world().resource::<TurnState>().turn(), 2);
+}
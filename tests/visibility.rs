@@ -0,0 +1,101 @@
+use bevy::prelude::*;
+use bevy_la_mesa::events::DrawToHand;
+use bevy_la_mesa::testing::{test_deck, TestCard, TestTable};
+use bevy_la_mesa::visibility::{CardVisibility, FaceHidden, LocalPlayer};
+use bevy_la_mesa::{Card, CardCover, CardFace};
+
+/// Two players holding one card each, one card left in the deck; every card has a face and
+/// a cover.
+fn table() -> TestTable {
+    let mut table = TestTable::new(2).with_deck(1, test_deck(3));
+
+    let world = table.app.world_mut();
+    let mut q_cards = world.query_filtered::<Entity, With<Card<TestCard>>>();
+    for card in q_cards.iter(world).collect::<Vec<_>>() {
+        world.spawn((CardFace, Visibility::Inherited, ChildOf(card)));
+        world.spawn((CardCover, Visibility::Hidden, ChildOf(card)));
+    }
+
+    let deck_entity = table.deck(1);
+    for player in 1..=2 {
+        table
+            .send(DrawToHand {
+                deck_entity,
+                num_cards: 1,
+                player,
+                timing: None,
+            })
+            .settle();
+    }
+    table
+}
+
+/// Whether the local player sees the face of the card, checking that face and cover agree.
+fn shown(table: &mut TestTable, key: &str) -> bool {
+    let card_entity = table.card(key);
+    let world = table.app.world();
+    let hidden = world.get::<FaceHidden>(card_entity).is_some();
+    for child in world.get::<Children>(card_entity).unwrap().iter() {
+        let visibility = world.get::<Visibility>(child).unwrap();
+        if world.get::<CardFace>(child).is_some() {
+            assert_eq!(*visibility == Visibility::Hidden, hidden, "face of {key}");
+        }
+        if world.get::<CardCover>(child).is_some() {
+            assert_eq!(*visibility == Visibility::Hidden, !hidden, "cover of {key}");
+        }
+    }
+    !hidden
+}
+
+fn look_as(table: &mut TestTable, player: usize) {
+    table.app.insert_resource(LocalPlayer(player));
+    table.update();
+}
+
+#[test]
+fn swapping_the_local_player_swaps_the_hidden_hands() {
+    let mut table = table();
+    assert_eq!(table.hand(1), ["card-3"]);
+    assert_eq!(table.hand(2), ["card-2"]);
+
+    assert!(shown(&mut table, "card-3"));
+    assert!(!shown(&mut table, "card-2"));
+    assert!(!shown(&mut table, "card-1"));
+
+    look_as(&mut table, 2);
+    assert!(!shown(&mut table, "card-3"));
+    assert!(shown(&mut table, "card-2"));
+    assert!(!shown(&mut table, "card-1"));
+
+    look_as(&mut table, 1);
+    assert!(shown(&mut table, "card-3"));
+    assert!(!shown(&mut table, "card-2"));
+}
+
+#[test]
+fn card_visibility_overrides_the_zone() {
+    let mut table = table();
+    let overrides = [
+        ("card-3", CardVisibility::Nobody),
+        ("card-2", CardVisibility::Players(vec![1])),
+        ("card-1", CardVisibility::Everyone),
+    ];
+    for (key, visibility) in overrides {
+        let card_entity = table.card(key);
+        table
+            .app
+            .world_mut()
+            .entity_mut(card_entity)
+            .insert(visibility);
+    }
+    table.update();
+
+    assert!(!shown(&mut table, "card-3"));
+    assert!(shown(&mut table, "card-2"));
+    assert!(shown(&mut table, "card-1"));
+
+    look_as(&mut table, 2);
+    assert!(!shown(&mut table, "card-3"));
+    assert!(!shown(&mut table, "card-2"));
+    assert!(shown(&mut table, "card-1"));
+}